@@ -0,0 +1,100 @@
+//! Rough benchmarks for the Intcode computer, run with `cargo bench`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use advent_of_code::intcode::compiled::CompiledComputer;
+use advent_of_code::intcode::{IntCodeComputer, MemoryKind};
+
+const BRANCHES: u32 = 10_000;
+
+/// Sums the numbers below 1,000,000 in a loop of three arithmetic instructions and a jump.
+const LOOP: &str =
+    "1101,0,0,100,1001,100,1,100,1,101,100,101,1007,100,1000000,102,1005,102,4,4,101,99";
+
+fn main() {
+    branching();
+    compiled();
+    memory();
+}
+
+/// Times `f` over `iterations` runs and prints the average.
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    let timer = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let average = timer.elapsed() / iterations;
+    println!("{name:<40} {:>12.1?}", average.max(Duration::from_nanos(1)));
+}
+
+/// Branching from a machine state, as e.g. a maze search does: a full clone per branch versus
+/// restoring a copy-on-write snapshot.
+fn branching() {
+    println!("Branch {BRANCHES}x from a machine with memory grown to:");
+    for size in [1_000, 100_000, 1_000_000] {
+        // Writes its input to the end of memory and halts
+        let mut computer = IntCodeComputer::new(&format!("3,{size},99"));
+        computer.set(size, 1);
+
+        bench(&format!("  {size:>7} cells, clone"), 1, || {
+            for branch in 0..BRANCHES {
+                let mut computer = black_box(&computer).clone();
+                computer.push_input(branch as i64);
+                computer.run().unwrap();
+                black_box(computer.get(size));
+            }
+        });
+
+        let start = computer.snapshot();
+        bench(
+            &format!("  {size:>7} cells, snapshot and restore"),
+            1,
+            || {
+                for branch in 0..BRANCHES {
+                    computer.restore(black_box(&start));
+                    computer.push_input(branch as i64);
+                    computer.run().unwrap();
+                    black_box(computer.snapshot());
+                }
+            },
+        );
+    }
+}
+
+/// A long running loop, interpreted versus compiled to closures.
+fn compiled() {
+    println!("Run a loop of 4,000,000 instructions:");
+    bench("  interpreted", 5, || {
+        let mut computer = IntCodeComputer::new(black_box(LOOP));
+        black_box(computer.run().unwrap());
+    });
+    bench("  compiled", 5, || {
+        let mut computer = CompiledComputer::new(IntCodeComputer::new(black_box(LOOP)));
+        black_box(computer.run().unwrap());
+    });
+}
+
+/// Dense memory wins for programs that stay close to their end, sparse memory once a program writes
+/// far beyond it.
+fn memory() {
+    println!("Run the loop with memory:");
+    for kind in [MemoryKind::Dense, MemoryKind::Sparse] {
+        bench(&format!("  {kind:?}"), 5, || {
+            let mut computer = IntCodeComputer::new(black_box(LOOP));
+            computer.set_memory_kind(kind);
+            black_box(computer.run().unwrap());
+        });
+    }
+
+    println!("Write a single cell past the end of memory with memory:");
+    for distance in [1_000, 100_000, 10_000_000] {
+        for kind in [MemoryKind::Dense, MemoryKind::Sparse] {
+            bench(&format!("  {distance:>8} cells away, {kind:?}"), 5, || {
+                let mut computer = IntCodeComputer::new(&format!("3,{distance},4,{distance},99"));
+                computer.set_memory_kind(kind);
+                computer.push_input(1);
+                black_box(computer.run().unwrap());
+            });
+        }
+    }
+}
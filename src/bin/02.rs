@@ -1,54 +1,77 @@
+use advent_of_code::intcode::sweep::Sweep;
+use advent_of_code::intcode::symbolic::SymbolicComputer;
+use advent_of_code::intcode::IntCodeComputer;
+
 advent_of_code::solution!(2);
 
+/// Day 2 programs run straight through, a candidate that executes this many instructions is stuck.
+const BUDGET: u64 = 10_000;
+
 pub fn part_one(input: &str) -> Option<u32> {
-    let mut code = load(input);
-    let result = run(&mut code, 12, 2);
+    let mut computer = IntCodeComputer::new(input);
+    let result = run(&mut computer, 12, 2)?;
 
     Some(result)
 }
 pub fn part_one_test(input: &str) -> Option<u32> {
-    let mut code = load(input);
+    let mut computer = IntCodeComputer::new(input);
 
-    let result = run(&mut code.clone(), *code.get(1).unwrap(), *code.get(2).unwrap());
+    let (noun, verb) = (computer.get(1) as u32, computer.get(2) as u32);
+    let result = run(&mut computer, noun, verb)?;
     Some(result)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let wanted_result = 19690720;
-    let code = load(input);
+    let computer = IntCodeComputer::new(input);
 
-    for noun in 0..=99 {
-        for verb in 0..=99 {
-            if run(&mut code.clone(), noun, verb) == wanted_result {
-                return Some(100 * noun + verb);
-            }
-        }
-    }
+    // The symbolic answer is confirmed by a concrete run, anything it got wrong falls back to the search
+    let found = solve(&computer, wanted_result)
+        .filter(|found| confirm(&computer, found, wanted_result))
+        .or_else(|| search(&computer, wanted_result))?;
+    Some(100 * found[0] as u32 + found[1] as u32)
+}
 
-    None
+/// Day 2 programs compute `a * noun + verb + c`, which can be solved for the wanted result directly.
+fn solve(computer: &IntCodeComputer, wanted_result: i64) -> Option<Vec<i64>> {
+    let mut symbolic = SymbolicComputer::new(computer);
+    symbolic.symbol(1, "noun");
+    symbolic.symbol(2, "verb");
+    symbolic.run().ok()?;
+    symbolic
+        .get(0)
+        .solve(wanted_result, &[("noun", 0..=99), ("verb", 0..=99)])
 }
 
-fn load(input: &str) -> Vec<u32> {
-    input.trim().split(',').map(|x| x.parse::<u32>().unwrap()).collect::<Vec<_>>()
+/// Whether running the program with the noun and verb in `found` gives the wanted result.
+fn confirm(computer: &IntCodeComputer, found: &[i64], wanted_result: i64) -> bool {
+    let mut computer = computer.clone();
+    found
+        .iter()
+        .zip(1..)
+        .for_each(|(&value, address)| computer.set(address, value));
+    computer.set_budget(Some(BUDGET));
+    computer.run().is_ok() && computer.get(0) == wanted_result
 }
 
-fn run(code: &mut Vec<u32>, noun: u32, verb: u32) -> u32 {
-    code[1] = noun;
-    code[2] = verb;
-    'main: for i in (0..code.len()).step_by(4) {
-        let op_code = code.get(i).unwrap();
-        let pos = &code[i + 1..i + 4].to_vec();
+/// Tries every noun and verb, for programs that do not compute a linear result.
+fn search(computer: &IntCodeComputer, wanted_result: i64) -> Option<Vec<i64>> {
+    let mut computer = computer.clone();
+    computer.set_budget(Some(BUDGET));
 
-        match op_code {
-            &1 => code[pos[2] as usize] = code[pos[0] as usize] + code[pos[1] as usize],
-            &2 => code[pos[2] as usize] = code[pos[0] as usize] * code[pos[1] as usize],
-            &99 => {
-                break 'main
-            }
-            _ => panic!("Unknown opCode {}", op_code)
-        }
-    }
-    code[0]
+    // Candidates that crash the program are simply skipped
+    Sweep::new(&computer)
+        .parameter(1, 0..=99)
+        .parameter(2, 0..=99)
+        .first(|computer| computer.get(0) == wanted_result)
+}
+
+fn run(computer: &mut IntCodeComputer, noun: u32, verb: u32) -> Option<u32> {
+    computer.set(1, noun as i64);
+    computer.set(2, verb as i64);
+    computer.set_budget(Some(BUDGET));
+    computer.run().ok()?;
+    Some(computer.get(0) as u32)
 }
 
 #[cfg(test)]
@@ -63,7 +86,10 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        // let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(true, true);
+        // A program of the same shape as the real inputs, computing `250000 * noun + verb + 16690718`
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(result, Some(1202));
     }
 }
@@ -0,0 +1,20 @@
+use advent_of_code::intcode::adventure::Explorer;
+use advent_of_code::intcode::IntCodeComputer;
+
+advent_of_code::solution!(25, 1);
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let mut explorer = Explorer::new(IntCodeComputer::new(input));
+    explorer.solve().inspect_err(|e| eprintln!("{e}")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(2424));
+    }
+}
@@ -0,0 +1,630 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::intcode::ascii::{AsciiComputer, AsciiOutput};
+use crate::intcode::{IntCodeComputer, IntcodeError};
+
+/// Items that end the game when taken, known from playing day 25.
+pub const DEADLY_ITEMS: &[&str] = &[
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+
+/// The room that weighs the droid, moving onto it with the wrong items ejects the droid back to the checkpoint.
+pub const PRESSURE_FLOOR: &str = "Pressure-Sensitive Floor";
+
+/// The most instructions a single command may take, taking the infinite loop never finishes.
+const BUDGET: u64 = 1_000_000;
+
+/// A room as described by the game.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+/// Parses every room described in a chunk of output, moving onto the pressure floor describes two rooms.
+pub fn parse_rooms(text: &str) -> Vec<Room> {
+    enum Section {
+        Description,
+        Doors,
+        Items,
+        Other,
+    }
+
+    let mut rooms: Vec<Room> = vec![];
+    let mut section = Section::Other;
+    for line in text.lines() {
+        if let Some(name) = line
+            .strip_prefix("== ")
+            .and_then(|line| line.strip_suffix(" =="))
+        {
+            rooms.push(Room {
+                name: name.to_string(),
+                ..Room::default()
+            });
+            section = Section::Description;
+            continue;
+        }
+        let Some(room) = rooms.last_mut() else {
+            continue;
+        };
+
+        match (line, &section) {
+            ("Doors here lead:", _) => section = Section::Doors,
+            ("Items here:", _) => section = Section::Items,
+            ("", Section::Description) => {}
+            ("", _) => section = Section::Other,
+            (line, Section::Description) => {
+                room.description = line.to_string();
+                section = Section::Other;
+            }
+            (line, Section::Doors) => room
+                .doors
+                .extend(line.strip_prefix("- ").map(str::to_string)),
+            (line, Section::Items) => room
+                .items
+                .extend(line.strip_prefix("- ").map(str::to_string)),
+            (_, Section::Other) => {}
+        }
+    }
+    rooms
+}
+
+/// Everything that can go wrong while exploring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdventureError {
+    Intcode(IntcodeError),
+    /// The game printed something the explorer does not understand, or halted where it should not.
+    Unexpected(String),
+    /// No door leads to the pressure floor.
+    NoCheckpoint,
+    /// No combination of the safe items gets past the pressure floor.
+    NoCombination,
+    /// There are too many safe items to try every combination of them.
+    TooManyItems(usize),
+}
+
+impl Error for AdventureError {}
+
+impl Display for AdventureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdventureError::Intcode(error) => write!(f, "{error}"),
+            AdventureError::Unexpected(text) => write!(f, "Adventure: unexpected output {text:?}"),
+            AdventureError::NoCheckpoint => {
+                write!(f, "Adventure: no door leads to the {PRESSURE_FLOOR}")
+            }
+            AdventureError::NoCombination => write!(
+                f,
+                "Adventure: no combination of items passes the {PRESSURE_FLOOR}"
+            ),
+            AdventureError::TooManyItems(count) => write!(
+                f,
+                "Adventure: {count} items are too many to try every combination"
+            ),
+        }
+    }
+}
+
+impl From<IntcodeError> for AdventureError {
+    fn from(error: IntcodeError) -> Self {
+        AdventureError::Intcode(error)
+    }
+}
+
+/// The map of the ship built by [`Explorer::explore`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Ship {
+    /// The room the exploration started in.
+    pub start: String,
+    pub rooms: BTreeMap<String, Room>,
+    /// Where the doors of each room lead, in the order the game lists them.
+    pub doors: BTreeMap<String, Vec<(String, String)>>,
+    /// Items that are safe to take, with the room they lie in, in the order they were found.
+    pub items: Vec<(String, String)>,
+    /// Items that end the game or keep the droid from moving.
+    pub deadly: Vec<String>,
+    /// The room and door leading onto the pressure floor.
+    pub checkpoint: Option<(String, String)>,
+}
+
+impl Ship {
+    /// The shortest list of doors from one room to another.
+    pub fn route(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut previous: HashMap<&str, (&str, &str)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(room) = queue.pop_front() {
+            if room == to {
+                let mut route = vec![];
+                let mut room = to;
+                while let Some(&(before, door)) = previous.get(room) {
+                    route.push(door.to_string());
+                    room = before;
+                }
+                route.reverse();
+                return Some(route);
+            }
+            for (door, next) in self.doors.get(room).into_iter().flatten() {
+                if next != from && !previous.contains_key(next.as_str()) {
+                    previous.insert(next, (room, door));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Plays the day 25 text adventure on its own.
+///
+/// The ship is mapped on copies of the computer that branch off snapshots of each room, so only the
+/// commands of the actual solution reach the main computer. Given the same program the explorer always
+/// sends the same commands, which makes the transcript reproducible.
+pub struct Explorer {
+    computer: AsciiComputer,
+    room: Option<Room>,
+    commands: Vec<String>,
+    transcript: String,
+}
+
+impl Explorer {
+    pub fn new(computer: IntCodeComputer) -> Self {
+        Self {
+            computer: AsciiComputer::new(computer),
+            room: None,
+            commands: vec![],
+            transcript: String::new(),
+        }
+    }
+
+    /// Everything the game printed and every command sent to it, as typed into `cargo ascii`.
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// The commands sent to the main computer so far.
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Maps every room reachable from the current one and sorts the items into safe and deadly ones.
+    pub fn explore(&mut self) -> Result<Ship, AdventureError> {
+        let start = self.current_room()?;
+        let mut ship = Ship {
+            start: start.name.clone(),
+            ..Ship::default()
+        };
+
+        let mut probe = self.computer.clone();
+        ship.rooms.insert(start.name.clone(), start.clone());
+        let mut stack = vec![(start, probe.computer_mut().snapshot())];
+        while let Some((room, snapshot)) = stack.pop() {
+            for item in &room.items {
+                probe.computer_mut().restore(&snapshot);
+                if DEADLY_ITEMS.contains(&item.as_str()) || is_deadly(&mut probe, item, &room) {
+                    ship.deadly.push(item.clone());
+                } else {
+                    ship.items.push((item.clone(), room.name.clone()));
+                }
+            }
+
+            for door in &room.doors {
+                probe.computer_mut().restore(&snapshot);
+                let output = command(&mut probe, Some(door))?;
+                let rooms = parse_rooms(&output.text);
+                match rooms.as_slice() {
+                    [floor, ..] if floor.name == PRESSURE_FLOOR => {
+                        ship.checkpoint = Some((room.name.clone(), door.clone()));
+                    }
+                    [.., next] if !output.halted => {
+                        ship.doors
+                            .entry(room.name.clone())
+                            .or_default()
+                            .push((door.clone(), next.name.clone()));
+                        if !ship.rooms.contains_key(&next.name) {
+                            ship.rooms.insert(next.name.clone(), next.clone());
+                            stack.push((next.clone(), probe.computer_mut().snapshot()));
+                        }
+                    }
+                    _ => return Err(AdventureError::Unexpected(output.text)),
+                }
+            }
+        }
+        Ok(ship)
+    }
+
+    /// Explores the ship, collects every safe item, and tries combinations of them on the pressure floor
+    /// until it lets the droid pass. Returns the password for the main airlock.
+    pub fn solve(&mut self) -> Result<u64, AdventureError> {
+        let ship = self.explore()?;
+        let (checkpoint, floor) = ship
+            .checkpoint
+            .clone()
+            .ok_or(AdventureError::NoCheckpoint)?;
+        let attempts = u32::try_from(ship.items.len())
+            .ok()
+            .and_then(|count| 1u64.checked_shl(count))
+            .ok_or(AdventureError::TooManyItems(ship.items.len()))?;
+
+        for (item, room) in &ship.items {
+            self.walk(&ship, room)?;
+            self.send(&format!("take {item}"))?;
+        }
+        self.walk(&ship, &checkpoint)?;
+
+        // Walk through every subset of the items in Gray code order, so each attempt takes or drops a single item
+        let mut carried = vec![true; ship.items.len()];
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                let index = attempt.trailing_zeros() as usize;
+                let action = if carried[index] { "drop" } else { "take" };
+                self.send(&format!("{action} {}", ship.items[index].0))?;
+                carried[index] = !carried[index];
+            }
+
+            let output = self.send(&floor)?;
+            if output.halted {
+                return password(&output.text).ok_or(AdventureError::Unexpected(output.text));
+            }
+        }
+        Err(AdventureError::NoCombination)
+    }
+
+    /// The room the droid is in, starting the game if it has not started yet.
+    fn current_room(&mut self) -> Result<Room, AdventureError> {
+        if self.room.is_none() {
+            let output = command(&mut self.computer, None)?;
+            self.transcript.push_str(&output.text);
+            self.room = parse_rooms(&output.text).pop();
+            if self.room.is_none() {
+                return Err(AdventureError::Unexpected(output.text));
+            }
+        }
+        Ok(self.room.clone().unwrap())
+    }
+
+    /// Walks the main computer to `room`.
+    fn walk(&mut self, ship: &Ship, room: &str) -> Result<(), AdventureError> {
+        let here = self.current_room()?.name;
+        let route = ship
+            .route(&here, room)
+            .ok_or_else(|| AdventureError::Unexpected(format!("no route to {room}")))?;
+        for door in route {
+            self.send(&door)?;
+        }
+        match &self.room {
+            Some(here) if here.name == room => Ok(()),
+            _ => Err(AdventureError::Unexpected(self.transcript.clone())),
+        }
+    }
+
+    /// Sends a command to the main computer and records it in the transcript.
+    fn send(&mut self, line: &str) -> Result<AsciiOutput, AdventureError> {
+        let output = command(&mut self.computer, Some(line))?;
+        self.commands.push(line.to_string());
+        self.transcript.push_str(line);
+        self.transcript.push('\n');
+        self.transcript.push_str(&output.text);
+        if let Some(room) = parse_rooms(&output.text).pop() {
+            self.room = Some(room);
+        }
+        Ok(output)
+    }
+}
+
+/// Sends a command and runs until the game asks for the next one, within the [`BUDGET`].
+fn command(computer: &mut AsciiComputer, line: Option<&str>) -> Result<AsciiOutput, IntcodeError> {
+    if let Some(line) = line {
+        computer.send_line(line);
+    }
+    computer.computer_mut().set_budget(Some(BUDGET));
+    let output = computer.run()?;
+    computer.computer_mut().set_budget(None);
+    Ok(output)
+}
+
+/// Whether taking `item` ends the game or keeps the droid from moving.
+fn is_deadly(computer: &mut AsciiComputer, item: &str, room: &Room) -> bool {
+    match command(computer, Some(&format!("take {item}"))) {
+        Ok(output) if !output.halted => match room.doors.first() {
+            Some(door) => match command(computer, Some(door)) {
+                Ok(output) => output.halted || parse_rooms(&output.text).is_empty(),
+                Err(_) => true,
+            },
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+/// The number the droid should type on the keypad, from the final message.
+fn password(text: &str) -> Option<u64> {
+    let (_, rest) = text.split_once("typing ")?;
+    rest.split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assembler::assemble;
+
+    /// A tiny adventure: a hull breach with a kitchen to the north and a lab to the east, behind which lies
+    /// the security checkpoint. Only carrying the mug (and not the coin) passes the floor, the hot coal melts you.
+    ///
+    /// Commands are recognized by the sum of their characters.
+    const ADVENTURE: &str = "
+                    JNZ #1, #look
+        ; prints the zero terminated string at [load_ptr], then jumps to [ret]
+        print:      DATA 1001
+        load_ptr:   DATA 0, 0, char
+                    JZ [char], [ret]
+                    OUT [char]
+                    ADD [load_ptr], #1 -> [load_ptr]
+                    JNZ #1, #print
+
+        look:       EQ [room], #0 -> [cond]
+                    JNZ [cond], #look_hull
+                    EQ [room], #1 -> [cond]
+                    JNZ [cond], #look_kitchen
+                    EQ [room], #2 -> [cond]
+                    JNZ [cond], #look_lab
+                    ADD #t_checkpoint, #0 -> [load_ptr]
+                    JNZ #1, #look_print
+        look_hull:  ADD #t_hull, #0 -> [load_ptr]
+                    JNZ #1, #look_print
+        look_kitchen: ADD #t_kitchen, #0 -> [load_ptr]
+                    JNZ #1, #look_print
+        look_lab:   ADD #t_lab, #0 -> [load_ptr]
+        look_print: ADD #items, #0 -> [ret]
+                    JNZ #1, #print
+
+        items:      EQ [mug], [room] -> [here_mug]
+                    EQ [coin], [room] -> [here_coin]
+                    EQ [coal], [room] -> [here_coal]
+                    ADD [here_mug], [here_coin] -> [cond]
+                    ADD [cond], [here_coal] -> [cond]
+                    JZ [cond], #prompt
+                    ADD #t_items, #0 -> [load_ptr]
+                    ADD #item_mug, #0 -> [ret]
+                    JNZ #1, #print
+        item_mug:   JZ [here_mug], #item_coin
+                    ADD #t_mug, #0 -> [load_ptr]
+                    ADD #item_coin, #0 -> [ret]
+                    JNZ #1, #print
+        item_coin:  JZ [here_coin], #item_coal
+                    ADD #t_coin, #0 -> [load_ptr]
+                    ADD #item_coal, #0 -> [ret]
+                    JNZ #1, #print
+        item_coal:  JZ [here_coal], #item_end
+                    ADD #t_coal, #0 -> [load_ptr]
+                    ADD #item_end, #0 -> [ret]
+                    JNZ #1, #print
+        item_end:   OUT #10
+
+        prompt:     ADD #t_prompt, #0 -> [load_ptr]
+                    ADD #read, #0 -> [ret]
+                    JNZ #1, #print
+        read:       ADD #0, #0 -> [sum]
+        read_loop:  IN -> [char]
+                    EQ [char], #10 -> [cond]
+                    JNZ [cond], #command
+                    ADD [sum], [char] -> [sum]
+                    JNZ #1, #read_loop
+
+        command:    EQ [sum], #555 -> [cond]
+                    JNZ [cond], #north
+                    EQ [sum], #563 -> [cond]
+                    JNZ [cond], #south
+                    EQ [sum], #429 -> [cond]
+                    JNZ [cond], #east
+                    EQ [sum], #451 -> [cond]
+                    JNZ [cond], #west
+                    EQ [sum], #782 -> [cond]
+                    JNZ [cond], #take_mug
+                    EQ [sum], #798 -> [cond]
+                    JNZ [cond], #drop_mug
+                    EQ [sum], #878 -> [cond]
+                    JNZ [cond], #take_coin
+                    EQ [sum], #894 -> [cond]
+                    JNZ [cond], #drop_coin
+                    EQ [sum], #1231 -> [cond]
+                    JNZ [cond], #take_coal
+                    JNZ #1, #blocked
+
+        north:      EQ [room], #0 -> [cond]
+                    JNZ [cond], #to_kitchen
+                    EQ [room], #3 -> [cond]
+                    JNZ [cond], #floor
+                    JNZ #1, #blocked
+        south:      EQ [room], #1 -> [cond]
+                    JNZ [cond], #to_hull
+                    JNZ #1, #blocked
+        east:       EQ [room], #0 -> [cond]
+                    JNZ [cond], #to_lab
+                    EQ [room], #2 -> [cond]
+                    JNZ [cond], #to_checkpoint
+                    JNZ #1, #blocked
+        west:       EQ [room], #2 -> [cond]
+                    JNZ [cond], #to_hull
+                    EQ [room], #3 -> [cond]
+                    JNZ [cond], #to_lab
+                    JNZ #1, #blocked
+        to_hull:    ADD #0, #0 -> [room]
+                    JNZ #1, #look
+        to_kitchen: ADD #1, #0 -> [room]
+                    JNZ #1, #look
+        to_lab:     ADD #2, #0 -> [room]
+                    JNZ #1, #look
+        to_checkpoint: ADD #3, #0 -> [room]
+                    JNZ #1, #look
+        blocked:    ADD #t_blocked, #0 -> [load_ptr]
+                    JNZ #1, #reply
+
+        take_mug:   EQ [mug], [room] -> [cond]
+                    JZ [cond], #missing
+                    ADD #-1, #0 -> [mug]
+                    ADD #t_take_mug, #0 -> [load_ptr]
+                    JNZ #1, #reply
+        drop_mug:   EQ [mug], #-1 -> [cond]
+                    JZ [cond], #not_carried
+                    ADD [room], #0 -> [mug]
+                    ADD #t_drop_mug, #0 -> [load_ptr]
+                    JNZ #1, #reply
+        take_coin:  EQ [coin], [room] -> [cond]
+                    JZ [cond], #missing
+                    ADD #-1, #0 -> [coin]
+                    ADD #t_take_coin, #0 -> [load_ptr]
+                    JNZ #1, #reply
+        drop_coin:  EQ [coin], #-1 -> [cond]
+                    JZ [cond], #not_carried
+                    ADD [room], #0 -> [coin]
+                    ADD #t_drop_coin, #0 -> [load_ptr]
+                    JNZ #1, #reply
+        take_coal:  EQ [coal], [room] -> [cond]
+                    JZ [cond], #missing
+                    ADD #t_melt, #0 -> [load_ptr]
+                    JNZ #1, #game_over
+        missing:    ADD #t_missing, #0 -> [load_ptr]
+                    JNZ #1, #reply
+        not_carried: ADD #t_not_carried, #0 -> [load_ptr]
+        reply:      ADD #prompt, #0 -> [ret]
+                    JNZ #1, #print
+
+        floor:      EQ [mug], #-1 -> [cond]
+                    JZ [cond], #alert
+                    EQ [coin], #-1 -> [cond]
+                    JNZ [cond], #alert
+                    ADD #t_cockpit, #0 -> [load_ptr]
+        game_over:  ADD #halt, #0 -> [ret]
+                    JNZ #1, #print
+        alert:      ADD #t_alert, #0 -> [load_ptr]
+                    ADD #look, #0 -> [ret]
+                    JNZ #1, #print
+        halt:       HLT
+
+        room:       DATA 0
+        mug:        DATA 1
+        coin:       DATA 2
+        coal:       DATA 2
+        here_mug:   DATA 0
+        here_coin:  DATA 0
+        here_coal:  DATA 0
+        char:       DATA 0
+        cond:       DATA 0
+        sum:        DATA 0
+        ret:        DATA 0
+    ";
+
+    const FLOOR: &str = "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\nDoors here lead:\n- south\n\nA loud, robotic voice says ";
+
+    const TEXTS: &[(&str, &str)] = &[
+        ("t_hull", "\n\n\n== Hull Breach ==\nYou got in through a hole in the floor here.\n\nDoors here lead:\n- north\n- east\n\n"),
+        ("t_kitchen", "\n\n\n== Kitchen ==\nEverything is frozen.\n\nDoors here lead:\n- south\n\n"),
+        ("t_lab", "\n\n\n== Science Lab ==\nYou see evidence here of prototype polymer design work.\n\nDoors here lead:\n- east\n- west\n\n"),
+        (
+            "t_checkpoint",
+            "\n\n\n== Security Checkpoint ==\nIn the next room, a pressure-sensitive floor will verify your identity.\n\nDoors here lead:\n- north\n- west\n\n",
+        ),
+        ("t_items", "Items here:\n"),
+        ("t_mug", "- mug\n"),
+        ("t_coin", "- coin\n"),
+        ("t_coal", "- hot coal\n"),
+        ("t_prompt", "Command?\n"),
+        ("t_blocked", "\nYou can't go that way.\n\n"),
+        ("t_take_mug", "\nYou take the mug.\n\n"),
+        ("t_drop_mug", "\nYou drop the mug.\n\n"),
+        ("t_take_coin", "\nYou take the coin.\n\n"),
+        ("t_drop_coin", "\nYou drop the coin.\n\n"),
+        ("t_missing", "\nYou don't see that item here.\n\n"),
+        ("t_not_carried", "\nYou don't have that item.\n\n"),
+        ("t_melt", "\nThe hot coal is way too hot! You melt!\n\n"),
+        ("t_alert", "\"Alert! Droids on this ship are heavier than the detected value!\" and you are ejected back to the checkpoint.\n"),
+        ("t_cockpit", "\"Analysis complete! You may proceed.\" and you enter the cockpit.\n\"Oh, hello! You should be able to get in by typing 2424 on the keypad at the main airlock.\"\n"),
+    ];
+
+    fn adventure() -> IntCodeComputer {
+        let mut source = ADVENTURE.to_string();
+        for (label, text) in TEXTS {
+            let text = if label == &"t_alert" || label == &"t_cockpit" {
+                format!("{FLOOR}{text}")
+            } else {
+                text.to_string()
+            };
+            let codes = text
+                .bytes()
+                .map(|code| code.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            source.push_str(&format!("{label}: DATA {codes}, 0\n"));
+        }
+        IntCodeComputer::new(&assemble(&source).unwrap())
+    }
+
+    #[test]
+    fn test_parse_rooms() {
+        let text = format!("{FLOOR}\"Alert!\" and you are ejected back to the checkpoint.\n{}Items here:\n- coin\n- hot coal\n\nCommand?\n", TEXTS[2].1);
+        let rooms = parse_rooms(&text);
+        assert_eq!(rooms.len(), 2);
+        assert_eq!(rooms[0].name, PRESSURE_FLOOR);
+        assert_eq!(rooms[0].doors, ["south"]);
+        assert_eq!(
+            rooms[1],
+            Room {
+                name: "Science Lab".into(),
+                description: "You see evidence here of prototype polymer design work.".into(),
+                doors: vec!["east".into(), "west".into()],
+                items: vec!["coin".into(), "hot coal".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_explore() {
+        let ship = Explorer::new(adventure()).explore().unwrap();
+        assert_eq!(ship.start, "Hull Breach");
+        assert_eq!(ship.rooms.len(), 4);
+        assert_eq!(
+            ship.items,
+            [
+                ("coin".into(), "Science Lab".into()),
+                ("mug".into(), "Kitchen".into())
+            ]
+        );
+        assert_eq!(ship.deadly, ["hot coal"]);
+        assert_eq!(
+            ship.checkpoint,
+            Some(("Security Checkpoint".into(), "north".into()))
+        );
+        assert_eq!(
+            ship.route("Kitchen", "Security Checkpoint"),
+            Some(vec!["south".into(), "east".into(), "east".into()])
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut explorer = Explorer::new(adventure());
+        assert_eq!(explorer.solve(), Ok(2424));
+        assert!(!explorer.commands().contains(&"take hot coal".to_string()));
+
+        // Replaying the commands reproduces the transcript
+        let mut computer = AsciiComputer::new(adventure());
+        let mut transcript = computer.run().unwrap().text;
+        for command in explorer.commands() {
+            computer.send_line(command);
+            transcript.push_str(&format!("{command}\n{}", computer.run().unwrap().text));
+        }
+        assert_eq!(transcript, explorer.transcript());
+        assert_eq!(Explorer::new(adventure()).solve(), Ok(2424));
+    }
+}
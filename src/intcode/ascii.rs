@@ -0,0 +1,113 @@
+use crate::intcode::{IntCodeComputer, IntcodeError, RunState};
+
+/// Converts text into input codes, one code per character.
+pub fn encode(text: &str) -> Vec<i64> {
+    text.chars().map(|c| c as i64).collect()
+}
+
+/// Converts output codes into text, values outside the ASCII range (e.g. the dust of day 17) are returned
+/// separately as numeric results.
+pub fn decode(values: &[i64]) -> (String, Vec<i64>) {
+    let mut text = String::new();
+    let mut numbers = vec![];
+    for &value in values {
+        match u8::try_from(value) {
+            Ok(code) if code.is_ascii() => text.push(code as char),
+            _ => numbers.push(value),
+        }
+    }
+    (text, numbers)
+}
+
+/// Everything an ASCII program printed until it waited for input or halted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AsciiOutput {
+    pub text: String,
+    /// Output values above 127, which are results rather than text.
+    pub numbers: Vec<i64>,
+    pub halted: bool,
+}
+
+/// An Intcode computer that speaks ASCII (days 17, 21 and 25).
+#[derive(Clone, Debug)]
+pub struct AsciiComputer {
+    computer: IntCodeComputer,
+}
+
+impl AsciiComputer {
+    pub fn new(computer: IntCodeComputer) -> Self {
+        Self { computer }
+    }
+
+    pub fn computer(&self) -> &IntCodeComputer {
+        &self.computer
+    }
+
+    pub fn computer_mut(&mut self) -> &mut IntCodeComputer {
+        &mut self.computer
+    }
+
+    /// Queues a line of input, the terminating newline is added.
+    pub fn send_line(&mut self, line: &str) {
+        encode(line)
+            .into_iter()
+            .for_each(|code| self.computer.push_input(code));
+        self.computer.push_input('\n' as i64);
+    }
+
+    /// Runs until the program waits for input or halts and returns everything it printed.
+    pub fn run(&mut self) -> Result<AsciiOutput, IntcodeError> {
+        let mut values = vec![];
+        let halted = loop {
+            match self.computer.resume()? {
+                RunState::Output(value) => values.push(value),
+                RunState::NeedsInput => break false,
+                RunState::Halted => break true,
+            }
+        };
+
+        let (text, numbers) = decode(&values);
+        Ok(AsciiOutput {
+            text,
+            numbers,
+            halted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let mut values = encode("#.#\n");
+        values.push(1234567);
+        assert_eq!(decode(&values), ("#.#\n".to_string(), vec![1234567]));
+    }
+
+    #[test]
+    fn test_conversation() {
+        // Prints "?\n", then echoes a single character of input, followed by 200
+        let program = "104,63,104,10,3,100,4,100,104,200,99";
+        let mut computer = AsciiComputer::new(IntCodeComputer::new(program));
+        assert_eq!(
+            computer.run(),
+            Ok(AsciiOutput {
+                text: "?\n".into(),
+                numbers: vec![],
+                halted: false
+            })
+        );
+
+        computer.send_line("y");
+        assert_eq!(
+            computer.run(),
+            Ok(AsciiOutput {
+                text: "y".into(),
+                numbers: vec![200],
+                halted: true
+            })
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::intcode::instruction::{Instruction, Mode, Opcode};
+
+/// An error raised while assembling, `line` is 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Error for AssembleError {}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// A value that is either written out literally or refers to the address of a label.
+enum Value {
+    Number(i64),
+    Label(String),
+}
+
+/// A parsed source line, ready to be encoded once all labels are known.
+enum Item {
+    Instruction {
+        opcode: Opcode,
+        operands: Vec<(Mode, Value)>,
+    },
+    Data(Vec<Value>),
+}
+
+impl Item {
+    fn length(&self) -> usize {
+        match self {
+            Item::Instruction { opcode, .. } => opcode.parameter_count() + 1,
+            Item::Data(values) => values.len(),
+        }
+    }
+}
+
+/// Assembles Intcode from its mnemonic text format into the comma separated format of [`IntCodeComputer::new`].
+///
+/// The format is the one produced by the disassembler, with labels and comments on top:
+///
+/// ```text
+/// start:  IN -> [value]          ; echo values until a zero is read
+///         JZ [value], #done
+///         OUT [value]
+///         JNZ #1, #start
+/// done:   HLT
+/// value:  DATA 0
+/// ```
+///
+/// Operands are positions `[12]`, immediates `#12` or relatives `[rb-3]`, each number can also be a label.
+/// The written operand follows `->`. A leading address like `0012:` is checked against the actual address.
+///
+/// [`IntCodeComputer::new`]: crate::intcode::IntCodeComputer::new
+pub fn assemble(source: &str) -> Result<String, AssembleError> {
+    let mut labels: HashMap<String, usize> = HashMap::new();
+    let mut items: Vec<(usize, Item)> = vec![];
+
+    // First pass: parse every line and determine the addresses of the labels
+    let mut address = 0;
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let error = |message: String| AssembleError {
+            line: number,
+            message,
+        };
+
+        let mut rest = line.split(';').next().unwrap().trim();
+        while let Some((head, tail)) = split_prefix(rest) {
+            if let Ok(expected) = head.parse::<usize>() {
+                if expected != address {
+                    return Err(error(format!(
+                        "address {expected} does not match actual address {address}"
+                    )));
+                }
+            } else if labels.insert(head.to_string(), address).is_some() {
+                return Err(error(format!("duplicate label \"{head}\"")));
+            }
+            rest = tail;
+        }
+        if rest.is_empty() {
+            continue;
+        }
+
+        let item = parse_item(rest).map_err(error)?;
+        address += item.length();
+        items.push((number, item));
+    }
+
+    // Second pass: resolve labels and encode
+    let mut memory: Vec<i64> = Vec::with_capacity(address);
+    for (number, item) in items {
+        let resolve = |value: &Value| match value {
+            Value::Number(number) => Ok(*number),
+            Value::Label(label) => match labels.get(label) {
+                Some(&address) => Ok(address as i64),
+                None => Err(AssembleError {
+                    line: number,
+                    message: format!("unknown label \"{label}\""),
+                }),
+            },
+        };
+
+        match item {
+            Item::Instruction { opcode, operands } => {
+                let mut modes = [Mode::Position; 3];
+                operands
+                    .iter()
+                    .zip(modes.iter_mut())
+                    .for_each(|((mode, _), slot)| *slot = *mode);
+                memory.push(Instruction { opcode, modes }.encode());
+                for (_, value) in &operands {
+                    memory.push(resolve(value)?);
+                }
+            }
+            Item::Data(values) => {
+                for value in &values {
+                    memory.push(resolve(value)?);
+                }
+            }
+        }
+    }
+
+    Ok(memory
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Splits off a leading `label:` or `0012:`, `None` if the line does not start with one.
+fn split_prefix(line: &str) -> Option<(&str, &str)> {
+    let (head, tail) = line.split_once(':')?;
+    let head = head.trim();
+    if head.is_empty() || !head.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some((head, tail.trim()))
+}
+
+fn parse_item(text: &str) -> Result<Item, String> {
+    let (mnemonic, arguments) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+
+    if mnemonic.eq_ignore_ascii_case("DATA") {
+        let values = split_list(arguments)
+            .into_iter()
+            .map(parse_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() {
+            return Err("DATA needs at least one value".to_string());
+        }
+        return Ok(Item::Data(values));
+    }
+
+    let opcode = Opcode::from_mnemonic(mnemonic)
+        .ok_or_else(|| format!("unknown mnemonic \"{mnemonic}\""))?;
+
+    let (sources, target) = match arguments.split_once("->") {
+        Some((sources, target)) => (sources, Some(target.trim())),
+        None => (arguments, None),
+    };
+    let mut operands = split_list(sources)
+        .into_iter()
+        .map(parse_operand)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match (opcode.writes(), target) {
+        (true, Some(target)) => operands.push(parse_operand(target)?),
+        (true, None) => return Err(format!("{} needs a target after \"->\"", opcode.mnemonic())),
+        (false, Some(_)) => return Err(format!("{} does not write a result", opcode.mnemonic())),
+        (false, None) => {}
+    }
+
+    if operands.len() != opcode.parameter_count() {
+        return Err(format!(
+            "{} takes {} operands, found {}",
+            opcode.mnemonic(),
+            opcode.parameter_count(),
+            operands.len()
+        ));
+    }
+
+    Ok(Item::Instruction { opcode, operands })
+}
+
+fn split_list(text: &str) -> Vec<&str> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn parse_operand(text: &str) -> Result<(Mode, Value), String> {
+    if let Some(value) = text.strip_prefix('#') {
+        return Ok((Mode::Immediate, parse_value(value)?));
+    }
+
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .ok_or_else(|| format!("invalid operand \"{text}\""))?
+        .trim();
+
+    // `[rb]`, `[rb+3]` and `[rb-3]` are relative, anything else (including labels starting with rb) is a position
+    match inner.strip_prefix("rb").map(str::trim) {
+        Some("") => Ok((Mode::Relative, Value::Number(0))),
+        Some(offset) if offset.starts_with('+') => Ok((Mode::Relative, parse_value(&offset[1..])?)),
+        Some(offset) if offset.starts_with('-') => Ok((Mode::Relative, parse_value(offset)?)),
+        _ => Ok((Mode::Position, parse_value(inner)?)),
+    }
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    if let Ok(number) = text.parse::<i64>() {
+        return Ok(Value::Number(number));
+    }
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Ok(Value::Label(text.to_string()));
+    }
+    Err(format!("invalid value \"{text}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::disassembler::disassemble;
+    use crate::intcode::{IntCodeComputer, Memory, MemoryKind};
+
+    #[test]
+    fn test_labels() {
+        let source = "
+            start:  IN -> [value]       ; read until a zero
+                    JZ [value], #done
+                    OUT [value]
+                    JNZ #1, #start
+            done:   HLT
+            value:  DATA 0
+        ";
+        let program = assemble(source).unwrap();
+        assert_eq!(program, "3,11,1006,11,10,4,11,1105,1,0,99,0");
+
+        let mut computer = IntCodeComputer::new(&program);
+        [4, 2, 0]
+            .into_iter()
+            .for_each(|value| computer.push_input(value));
+        assert_eq!(computer.run().unwrap(), &[4, 2]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let computer = IntCodeComputer::new(program);
+        let listing = disassemble(computer.memory())
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(assemble(&listing.join("\n")).unwrap(), program);
+
+        // Cells of the program that hold 0 are listed for sparse memory as well
+        let program = "1101,0,0,9,4,9,99,0,0,0";
+        let memory = Memory::new(
+            program
+                .split(',')
+                .map(|value| value.parse().unwrap())
+                .collect(),
+            MemoryKind::Sparse,
+        );
+        let listing = disassemble(&memory)
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(assemble(&listing.join("\n")).unwrap(), program);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            assemble("ADD #1, #2").unwrap_err(),
+            AssembleError {
+                line: 1,
+                message: "ADD needs a target after \"->\"".into()
+            }
+        );
+        assert_eq!(assemble("HLT\nJMP #0").unwrap_err().line, 2);
+        assert_eq!(
+            assemble("OUT [missing]").unwrap_err().message,
+            "unknown label \"missing\""
+        );
+        assert_eq!(assemble("0001: HLT").unwrap_err().line, 1);
+    }
+}
@@ -0,0 +1,537 @@
+use std::sync::Arc;
+
+use crate::intcode::instruction::{Instruction, Mode, Opcode};
+use crate::intcode::{IntCodeComputer, IntcodeError, RunState};
+
+/// A pre-decoded instruction, returns the address it wrote to.
+type Op = Box<dyn Fn(&mut IntCodeComputer) -> Result<Option<usize>, IntcodeError> + Send + Sync>;
+
+/// A run of straight-line instructions, optionally ending in a jump. It ends before the first input,
+/// output or halt, those are executed by the interpreter.
+struct Block {
+    /// Every instruction before the jump with its address.
+    ops: Vec<(usize, Op)>,
+    /// The address after the last instruction before the jump.
+    end: usize,
+    jump: Option<Jump>,
+}
+
+impl Block {
+    /// The address after the last instruction, including the jump.
+    fn code_end(&self) -> usize {
+        self.end + self.jump.as_ref().map_or(0, |_| 3)
+    }
+}
+
+/// A compiled jump-if-true or jump-if-false.
+struct Jump {
+    site: Site,
+    if_true: bool,
+    condition: Operand,
+    target: Operand,
+}
+
+impl Jump {
+    /// The address execution continues at.
+    fn next(&self, computer: &IntCodeComputer) -> Result<usize, IntcodeError> {
+        if (self.condition.read(computer, self.site)? != 0) != self.if_true {
+            return Ok(self.site.instruction_pointer + 3);
+        }
+        let target = self.target.read(computer, self.site)?;
+        let Site {
+            instruction_pointer,
+            instruction,
+        } = self.site;
+        usize::try_from(target).map_err(|_| IntcodeError::NegativeJump {
+            instruction_pointer,
+            instruction,
+            target,
+        })
+    }
+}
+
+/// What is known about the code starting at an address.
+#[derive(Clone, Default)]
+enum Slot {
+    #[default]
+    Unknown,
+    Interpret,
+    Compiled(Arc<Block>),
+}
+
+/// The location of an instruction, to report errors exactly like the interpreter.
+#[derive(Clone, Copy)]
+struct Site {
+    instruction_pointer: usize,
+    instruction: i64,
+}
+
+impl Site {
+    fn overflow(self) -> IntcodeError {
+        IntcodeError::Overflow {
+            instruction_pointer: self.instruction_pointer,
+            instruction: self.instruction,
+        }
+    }
+}
+
+/// A decoded parameter.
+#[derive(Clone, Copy)]
+enum Operand {
+    Immediate(i64),
+    Position(usize),
+    Relative(i64),
+}
+
+impl Operand {
+    /// `None` for a position before address 0, which is left to the interpreter to report.
+    fn new(raw: i64, mode: Mode) -> Option<Self> {
+        match mode {
+            Mode::Immediate => Some(Operand::Immediate(raw)),
+            Mode::Position => usize::try_from(raw).ok().map(Operand::Position),
+            Mode::Relative => Some(Operand::Relative(raw)),
+        }
+    }
+
+    fn address(self, computer: &IntCodeComputer, site: Site) -> Result<usize, IntcodeError> {
+        let Site {
+            instruction_pointer,
+            instruction,
+        } = site;
+        match self {
+            Operand::Position(address) => Ok(address),
+            Operand::Relative(offset) => {
+                let address = computer
+                    .relative_base()
+                    .checked_add(offset)
+                    .ok_or(site.overflow())?;
+                usize::try_from(address).map_err(|_| IntcodeError::NegativeAddress {
+                    instruction_pointer,
+                    instruction,
+                    address,
+                })
+            }
+            Operand::Immediate(_) => Err(IntcodeError::ImmediateWrite {
+                instruction_pointer,
+                instruction,
+            }),
+        }
+    }
+
+    fn read(self, computer: &IntCodeComputer, site: Site) -> Result<i64, IntcodeError> {
+        match self {
+            Operand::Immediate(value) => Ok(value),
+            _ => Ok(computer.get(self.address(computer, site)?)),
+        }
+    }
+}
+
+/// An Intcode computer that compiles straight-line blocks of instructions into closures the first time
+/// they are reached, so they are not decoded again on every execution. Input, output and halts are left
+/// to the interpreter, as are jumps while loops are detected.
+///
+/// Writes into compiled code discard the affected blocks, the modified addresses are interpreted from
+/// then on. Results, errors, budgets and loop detection behave exactly like [`IntCodeComputer::resume`].
+/// A computer that is being profiled or uses sparse memory is interpreted completely.
+#[derive(Clone)]
+pub struct CompiledComputer {
+    computer: IntCodeComputer,
+    /// Compiled blocks by start address.
+    blocks: Vec<Slot>,
+    /// Addresses covered by a compiled block.
+    code: Vec<bool>,
+    /// Code addresses the program wrote to, never compiled again.
+    modified: Vec<bool>,
+}
+
+impl CompiledComputer {
+    pub fn new(computer: IntCodeComputer) -> Self {
+        Self {
+            computer,
+            blocks: vec![],
+            code: vec![],
+            modified: vec![],
+        }
+    }
+
+    pub fn computer(&self) -> &IntCodeComputer {
+        &self.computer
+    }
+
+    /// Access to the computer, e.g. to patch memory. All compiled code is discarded.
+    pub fn computer_mut(&mut self) -> &mut IntCodeComputer {
+        self.blocks.clear();
+        self.code.clear();
+        &mut self.computer
+    }
+
+    pub fn into_inner(self) -> IntCodeComputer {
+        self.computer
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.computer.push_input(value);
+    }
+
+    /// Runs until the program halts, see [`IntCodeComputer::run`].
+    pub fn run(&mut self) -> Result<&[i64], IntcodeError> {
+        loop {
+            match self.resume()? {
+                RunState::Output(_) => continue,
+                RunState::NeedsInput => {
+                    let instruction_pointer = self.computer.instruction_pointer();
+                    let instruction = self.computer.get(instruction_pointer);
+                    return Err(IntcodeError::InputExhausted {
+                        instruction_pointer,
+                        instruction,
+                    });
+                }
+                RunState::Halted => break,
+            }
+        }
+        Ok(self.computer.output())
+    }
+
+    /// Runs until the program needs input, produces output or halts, see [`IntCodeComputer::resume`].
+    pub fn resume(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Executes a compiled block, or a single instruction where the interpreter is used.
+    fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let start = self.computer.instruction_pointer();
+        // Only code within dense memory is cached, so jumps far beyond the program allocate nothing
+        if self.computer.profile().is_some()
+            || start >= self.computer.memory().len()
+            || self.computer.memory().is_sparse()
+        {
+            return self.interpret();
+        }
+        if let None | Some(Slot::Unknown) = self.blocks.get(start) {
+            let slot = self
+                .compile(start)
+                .map_or(Slot::Interpret, |block| Slot::Compiled(Arc::new(block)));
+            if self.blocks.len() <= start {
+                self.blocks.resize(start + 1, Slot::Unknown);
+            }
+            self.blocks[start] = slot;
+        }
+        let Slot::Compiled(block) = &self.blocks[start] else {
+            return self.interpret();
+        };
+        let length = block.ops.len() as u64 + block.jump.is_some() as u64;
+        // Leave running out of budget to the interpreter
+        if self.computer.budget().is_some_and(|budget| budget < length) {
+            return self.interpret();
+        }
+
+        let computer = &mut self.computer;
+        for (index, (address, op)) in block.ops.iter().enumerate() {
+            match op(computer) {
+                Err(error) => {
+                    computer.advance(*address, index as u64);
+                    return Err(error);
+                }
+                Ok(Some(written)) if self.code.get(written).copied().unwrap_or(false) => {
+                    let next = block
+                        .ops
+                        .get(index + 1)
+                        .map_or(block.end, |(address, _)| *address);
+                    computer.advance(next, index as u64 + 1);
+                    self.invalidate(written);
+                    return Ok(None);
+                }
+                Ok(_) => {}
+            }
+        }
+
+        let next = match &block.jump {
+            Some(jump) => jump
+                .next(computer)
+                .inspect_err(|_| computer.advance(block.end, length - 1))?,
+            None => block.end,
+        };
+        computer.advance(next, length);
+        Ok(None)
+    }
+
+    /// Executes a single instruction with the interpreter.
+    fn interpret(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let target = self.write_target()?;
+        let state = self.computer.step()?;
+        if let Some(target) = target.filter(|&target| self.is_code(target)) {
+            self.invalidate(target);
+        }
+        Ok(state)
+    }
+
+    /// The address the instruction at the instruction pointer writes to, if any.
+    fn write_target(&self) -> Result<Option<usize>, IntcodeError> {
+        let instruction_pointer = self.computer.instruction_pointer();
+        let raw_instruction = self.computer.get(instruction_pointer);
+        let Some(instruction) =
+            Instruction::decode(raw_instruction).filter(|instruction| instruction.opcode.writes())
+        else {
+            return Ok(None);
+        };
+        let index = instruction.opcode.parameter_count() - 1;
+        let raw = self.computer.get(instruction_pointer + 1 + index);
+        let address =
+            match instruction.modes[index] {
+                Mode::Position => raw,
+                Mode::Relative => self.computer.relative_base().checked_add(raw).ok_or(
+                    IntcodeError::Overflow {
+                        instruction_pointer,
+                        instruction: raw_instruction,
+                    },
+                )?,
+                Mode::Immediate => return Ok(None),
+            };
+        Ok(usize::try_from(address).ok())
+    }
+
+    /// Compiles the straight-line instructions from `start`, `None` if the first one is left to the interpreter.
+    fn compile(&mut self, start: usize) -> Option<Block> {
+        let mut ops = vec![];
+        let mut end = start;
+        while let Some((op, length)) = self.compile_instruction(end) {
+            ops.push((end, op));
+            end += length;
+        }
+        let block = Block {
+            jump: self.compile_jump(end),
+            ops,
+            end,
+        };
+        if block.ops.is_empty() && block.jump.is_none() {
+            return None;
+        }
+
+        let code_end = block.code_end();
+        if self.code.len() < code_end {
+            self.code.resize(code_end, false);
+        }
+        self.code[start..code_end].fill(true);
+        Some(block)
+    }
+
+    /// Compiles the jump at `address`, `None` if it is not a valid jump or loops are detected, as the
+    /// interpreter tracks the state after every jump.
+    fn compile_jump(&self, address: usize) -> Option<Jump> {
+        let raw = self.computer.get(address);
+        let instruction = Instruction::decode(raw)?;
+        let if_true = match instruction.opcode {
+            Opcode::JumpIfTrue => true,
+            Opcode::JumpIfFalse => false,
+            _ => return None,
+        };
+        if self.computer.detects_loops() || self.is_modified(address, instruction.length()) {
+            return None;
+        }
+
+        let operand = |index: usize| {
+            Operand::new(
+                self.computer.get(address + 1 + index),
+                instruction.modes[index],
+            )
+        };
+        let site = Site {
+            instruction_pointer: address,
+            instruction: raw,
+        };
+        Some(Jump {
+            site,
+            if_true,
+            condition: operand(0)?,
+            target: operand(1)?,
+        })
+    }
+
+    /// Compiles the arithmetic, comparison or relative base instruction at `address` and returns its length.
+    fn compile_instruction(&self, address: usize) -> Option<(Op, usize)> {
+        let raw = self.computer.get(address);
+        let instruction = Instruction::decode(raw)?;
+        let length = instruction.length();
+        if self.is_modified(address, length) {
+            return None;
+        }
+
+        let site = Site {
+            instruction_pointer: address,
+            instruction: raw,
+        };
+        let operand = |index: usize| {
+            Operand::new(
+                self.computer.get(address + 1 + index),
+                instruction.modes[index],
+            )
+        };
+        let op: Op = match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let (a, b, target) = (operand(0)?, operand(1)?, operand(2)?);
+                if let Operand::Immediate(_) = target {
+                    return None;
+                }
+                let function: fn(i64, i64) -> Option<i64> = match instruction.opcode {
+                    Opcode::Add => i64::checked_add,
+                    Opcode::Multiply => i64::checked_mul,
+                    Opcode::LessThan => |a, b| Some((a < b) as i64),
+                    _ => |a, b| Some((a == b) as i64),
+                };
+                Box::new(move |computer| {
+                    let value = function(a.read(computer, site)?, b.read(computer, site)?)
+                        .ok_or(site.overflow())?;
+                    let address = target.address(computer, site)?;
+                    computer.set(address, value);
+                    Ok(Some(address))
+                })
+            }
+            Opcode::AdjustRelativeBase => {
+                let a = operand(0)?;
+                Box::new(move |computer| {
+                    let relative_base = computer
+                        .relative_base()
+                        .checked_add(a.read(computer, site)?)
+                        .ok_or(site.overflow())?;
+                    computer.set_relative_base(relative_base);
+                    Ok(None)
+                })
+            }
+            _ => return None,
+        };
+        Some((op, length))
+    }
+
+    fn is_code(&self, address: usize) -> bool {
+        self.code.get(address).copied().unwrap_or(false)
+    }
+
+    /// Whether the program wrote to any of the `length` cells from `address`.
+    fn is_modified(&self, address: usize, length: usize) -> bool {
+        (address..address + length)
+            .any(|address| self.modified.get(address).copied().unwrap_or(false))
+    }
+
+    /// The program wrote into compiled code at `address`, discard the blocks covering it and interpret
+    /// the address from now on.
+    fn invalidate(&mut self, address: usize) {
+        for (start, slot) in self.blocks.iter_mut().enumerate() {
+            if matches!(slot, Slot::Compiled(block) if (start..block.code_end()).contains(&address))
+            {
+                *slot = Slot::Unknown;
+            }
+        }
+        self.code[address] = false;
+        if self.modified.len() <= address {
+            self.modified.resize(address + 1, false);
+        }
+        self.modified[address] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example programs of days 2, 5 and 9 with the inputs to feed them.
+    const EXAMPLES: &[(&str, &[i64])] = &[
+        ("1,9,10,3,2,3,11,0,99,30,40,50", &[]),
+        ("1,1,1,4,99,5,6,0,99", &[]),
+        ("1002,4,3,4,33", &[]),
+        ("3,9,8,9,10,9,4,9,99,-1,8", &[8]),
+        ("3,3,1107,-1,8,3,4,3,99", &[5]),
+        ("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", &[0]),
+        ("3,3,1105,-1,9,1101,0,0,12,4,12,99,1", &[3]),
+        (
+            "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+             999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+            &[9],
+        ),
+        ("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99", &[]),
+        ("1102,34915192,34915192,7,4,7,99,0", &[]),
+        ("104,1125899906842624,99", &[]),
+    ];
+
+    /// Runs the interpreter and the compiled tier side by side and compares every observable result.
+    fn assert_same(program: &str, input: &[i64], budget: Option<u64>) {
+        let mut interpreted = IntCodeComputer::new(program);
+        interpreted.set_budget(budget);
+        input
+            .iter()
+            .for_each(|&value| interpreted.push_input(value));
+        let mut compiled = CompiledComputer::new(interpreted.clone());
+
+        let expected = interpreted.run().map(<[i64]>::to_vec);
+        let result = compiled.run().map(<[i64]>::to_vec);
+        let compiled = compiled.computer();
+        assert_eq!(result, expected, "{program}");
+        assert_eq!(compiled.memory(), interpreted.memory(), "{program}");
+        assert_eq!(
+            compiled.instruction_pointer(),
+            interpreted.instruction_pointer(),
+            "{program}"
+        );
+        assert_eq!(
+            compiled.relative_base(),
+            interpreted.relative_base(),
+            "{program}"
+        );
+        assert_eq!(compiled.executed(), interpreted.executed(), "{program}");
+    }
+
+    #[test]
+    fn test_examples() {
+        for (program, input) in EXAMPLES {
+            assert_same(program, input, None);
+        }
+    }
+
+    #[test]
+    fn test_budgets() {
+        // Running out of budget anywhere, also in the middle of a block
+        for (program, input) in EXAMPLES {
+            for budget in 0..20 {
+                assert_same(program, input, Some(budget));
+            }
+        }
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        // The first instruction turns the ADD at 8 into a MUL before the block reaches it
+        assert_same("1101,1,1,8,1101,0,0,20,1101,3,4,21,4,21,99", &[], None);
+        // The input overwrites the opcode of a compiled instruction, switching between ADD and MUL
+        assert_same(
+            "1101,1,1,20,3,6,1101,5,6,21,4,21,1105,1,4,99",
+            &[1101, 1102, 1101],
+            None,
+        );
+    }
+
+    #[test]
+    fn test_loop_detection() {
+        let program = "1001,14,1,14,1007,14,3,15,1005,15,0,1105,1,11,0,0";
+        let mut interpreted = IntCodeComputer::new(program);
+        interpreted.detect_loops(true);
+        let mut compiled = CompiledComputer::new(interpreted.clone());
+        assert_eq!(
+            compiled.run().map(<[i64]>::to_vec),
+            interpreted.run().map(<[i64]>::to_vec)
+        );
+        assert!(compiled.run().is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        // A negative relative address in the middle of a block
+        assert_same("1101,1,1,20,109,-5,22201,0,0,0,99", &[], None);
+        // An immediate write, left to the interpreter
+        assert_same("1101,1,1,20,11101,1,1,1,99", &[], None);
+        // A jump to a negative address
+        assert_same("1101,1,1,20,1105,1,-3,99", &[], None);
+    }
+}
@@ -0,0 +1,792 @@
+use std::collections::VecDeque;
+
+use crate::intcode::instruction::{Instruction, Mode, Opcode};
+use crate::intcode::io::{IntcodeInput, IntcodeOutput};
+use crate::intcode::loops::LoopDetector;
+use crate::intcode::memory::{Backend, Memory, MemoryKind};
+use crate::intcode::profile::{self, Profile};
+use crate::intcode::snapshot::{PageTracker, Pages, Snapshot};
+use crate::intcode::IntcodeError;
+
+/// The reason a running Intcode computer handed control back to the caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    /// An input instruction was reached with no queued input, push input and resume.
+    NeedsInput,
+    /// An output instruction produced a value.
+    Output(i64),
+    /// The program reached opcode 99.
+    Halted,
+}
+
+/// An Intcode computer holding a program in memory together with its queued input and produced output.
+#[derive(Debug)]
+pub struct IntCodeComputer {
+    memory: Memory,
+    instruction_pointer: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    output: Vec<i64>,
+    /// The amount of instructions that may still be executed, unlimited if `None`.
+    budget: Option<u64>,
+    executed: u64,
+    loop_detector: Option<Box<LoopDetector>>,
+    /// Tracks changed pages of dense memory once the first snapshot is taken.
+    pages: Option<Box<PageTracker>>,
+    profile: Option<Box<Profile>>,
+}
+
+impl IntCodeComputer {
+    /// Loads a comma separated Intcode program, panics if it holds anything but integers.
+    /// Use [`IntCodeComputer::parse`] for programs that may be malformed.
+    pub fn new(program: &str) -> Self {
+        Self::parse(program).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Loads a comma separated Intcode program, failing with [`IntcodeError::Parse`] on the first value
+    /// that is not an integer.
+    pub fn parse(program: &str) -> Result<Self, IntcodeError> {
+        let memory = program
+            .trim()
+            .split(',')
+            .enumerate()
+            .map(|(index, value)| {
+                value
+                    .trim()
+                    .parse::<i64>()
+                    .map_err(|_| IntcodeError::Parse { index })
+            })
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(Self::from_memory(Memory::from(memory)))
+    }
+
+    /// A computer at the start of `memory`.
+    pub(crate) fn from_memory(memory: Memory) -> Self {
+        let profile = profile::enabled().then(|| Box::new(Profile::new(&memory)));
+        Self {
+            memory,
+            instruction_pointer: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            output: vec![],
+            budget: None,
+            executed: 0,
+            loop_detector: None,
+            pages: None,
+            profile,
+        }
+    }
+
+    /// Reads the value stored at `address`, memory beyond the program reads as 0.
+    pub fn get(&self, address: usize) -> i64 {
+        self.memory.get(address)
+    }
+
+    /// Overwrites the value stored at `address`, e.g. to set the noun and verb of day 2.
+    /// Memory grows to fit addresses beyond the program.
+    pub fn set(&mut self, address: usize, value: i64) {
+        if let Some(detector) = &mut self.loop_detector {
+            detector.write(address, self.memory.get(address), value);
+        }
+        self.memory.set(address, value);
+        if self.memory.is_sparse() {
+            // Sparse memory shares its pages by itself
+            self.pages = None;
+        } else if let Some(pages) = &mut self.pages {
+            pages.write(address);
+        }
+    }
+
+    /// Chooses how memory is stored, [`MemoryKind::Auto`] by default. Both kinds behave identically,
+    /// dense memory is faster while sparse memory only allocates the pages that were written, so
+    /// programs can address far beyond their length.
+    pub fn set_memory_kind(&mut self, kind: MemoryKind) {
+        self.memory.set_kind(kind);
+        self.pages = None;
+    }
+
+    /// Captures the complete state of the computer. Memory pages are shared with earlier snapshots,
+    /// so only the pages written since the last snapshot or restore are copied.
+    pub fn snapshot(&mut self) -> Snapshot {
+        let memory = match self.memory.backend() {
+            Backend::Dense(cells) => {
+                let pages = self
+                    .pages
+                    .get_or_insert_with(|| Box::new(PageTracker::new(cells)));
+                Pages::Dense {
+                    pages: pages.sync(cells),
+                    length: cells.len(),
+                }
+            }
+            Backend::Sparse(sparse) => Pages::Sparse(sparse.clone()),
+        };
+        Snapshot {
+            memory,
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            budget: self.budget,
+            executed: self.executed,
+        }
+    }
+
+    /// Returns the computer to the state of `snapshot`, only copying the memory pages that differ.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        match &snapshot.memory {
+            Pages::Dense { pages, length } if self.memory.kind() == MemoryKind::Sparse => {
+                let cells = pages
+                    .iter()
+                    .flat_map(|page| page.iter().copied())
+                    .take(*length)
+                    .collect();
+                self.memory = Memory::new(cells, MemoryKind::Sparse);
+            }
+            Pages::Dense { pages, length } => {
+                if self.memory.is_sparse() {
+                    self.memory = Memory::new(vec![], self.memory.kind());
+                }
+                let Backend::Dense(cells) = self.memory.backend_mut() else {
+                    unreachable!()
+                };
+                let tracker = self
+                    .pages
+                    .get_or_insert_with(|| Box::new(PageTracker::new(cells)));
+                tracker.restore(cells, pages, *length);
+            }
+            Pages::Sparse(sparse) => {
+                *self.memory.backend_mut() = Backend::Sparse(sparse.clone());
+                self.pages = None;
+            }
+        }
+        self.instruction_pointer = snapshot.instruction_pointer;
+        self.relative_base = snapshot.relative_base;
+        self.input.clone_from(&snapshot.input);
+        self.output.clone_from(&snapshot.output);
+        self.budget = snapshot.budget;
+        self.executed = snapshot.executed;
+        if self.loop_detector.is_some() {
+            self.detect_loops(true);
+        }
+    }
+
+    /// Limits the amount of instructions that may still be executed, once used up execution fails with
+    /// [`IntcodeError::BudgetExhausted`]. `None` removes the limit.
+    pub fn set_budget(&mut self, budget: Option<u64>) {
+        self.budget = budget;
+    }
+
+    /// The amount of instructions executed so far.
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Enables detection of provable infinite loops, execution then fails with [`IntcodeError::InfiniteLoop`]
+    /// as soon as the computer returns to an earlier state without any input or output in between.
+    /// This costs a little on every write and jump, so it is disabled by default.
+    pub fn detect_loops(&mut self, enabled: bool) {
+        self.loop_detector = enabled.then(|| Box::new(LoopDetector::new(&self.memory)));
+    }
+
+    /// Enables the profiler, see [`IntCodeComputer::profile`]. Profiling is enabled for every computer
+    /// when [`profile::PROFILE_VAR`] is set, e.g. by `cargo solve --profile`.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profile = enabled.then(|| Box::new(Profile::new(&self.memory)));
+    }
+
+    /// What the computer executed and touched since profiling was enabled.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_deref()
+    }
+
+    /// The complete memory, including any cells beyond the program that were written to.
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    /// The current relative base used by relative mode parameters.
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    /// Moves the relative base, e.g. from a debugger.
+    pub fn set_relative_base(&mut self, relative_base: i64) {
+        self.relative_base = relative_base;
+    }
+
+    /// Values queued for upcoming input instructions.
+    pub fn pending_input(&self) -> impl Iterator<Item = &i64> {
+        self.input.iter()
+    }
+
+    /// Queues a value to be consumed by the next input instruction.
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+
+    /// All values written by output instructions so far.
+    pub fn output(&self) -> &[i64] {
+        &self.output
+    }
+
+    /// The address of the next instruction to execute.
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    /// Runs the program until it halts and returns all produced output.
+    /// Fails with [`IntcodeError::InputExhausted`] when the program asks for more input than was queued.
+    pub fn run(&mut self) -> Result<&[i64], IntcodeError> {
+        loop {
+            match self.resume()? {
+                RunState::Output(_) => continue,
+                RunState::NeedsInput => {
+                    return Err(IntcodeError::InputExhausted {
+                        instruction_pointer: self.instruction_pointer,
+                        instruction: self.get(self.instruction_pointer),
+                    })
+                }
+                RunState::Halted => break,
+            }
+        }
+
+        Ok(&self.output)
+    }
+
+    /// Runs the program until it halts, reading input from `input` once the queued input ran out and
+    /// writing every output value to `output`. Fails with [`IntcodeError::InputExhausted`] when `input` runs dry.
+    pub fn run_with(
+        &mut self,
+        mut input: impl IntcodeInput,
+        mut output: impl IntcodeOutput,
+    ) -> Result<(), IntcodeError> {
+        loop {
+            match self.resume()? {
+                RunState::Output(value) => output.write(value),
+                RunState::NeedsInput => match input.read() {
+                    Some(value) => self.push_input(value),
+                    None => {
+                        return Err(IntcodeError::InputExhausted {
+                            instruction_pointer: self.instruction_pointer,
+                            instruction: self.get(self.instruction_pointer),
+                        })
+                    }
+                },
+                RunState::Halted => return Ok(()),
+            }
+        }
+    }
+
+    /// Runs the program until it produces output, needs input or halts.
+    /// The computer keeps its state, so it can be resumed after e.g. pushing more input.
+    pub fn resume(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Executes a single instruction, returns the state if the instruction hands control back to the caller.
+    /// An input instruction without queued input and opcode 99 leave the instruction pointer in place,
+    /// as does an instruction that fails.
+    pub fn step(&mut self) -> Result<Option<RunState>, IntcodeError> {
+        let instruction = self.decode()?;
+        if self.budget == Some(0) && instruction.opcode != Opcode::Halt {
+            let instruction_pointer = self.instruction_pointer;
+            return Err(IntcodeError::BudgetExhausted {
+                instruction_pointer,
+                instruction: self.get(instruction_pointer),
+            });
+        }
+
+        // Resolved before executing, as the instruction may change its own parameters
+        let accesses = self.profile.is_some().then(|| self.accesses(&instruction));
+
+        let mut state = None;
+        let mut next = self.instruction_pointer + instruction.length();
+        match instruction.opcode {
+            Opcode::Add => {
+                let value = self
+                    .parameter(&instruction, 0)?
+                    .checked_add(self.parameter(&instruction, 1)?);
+                self.write(&instruction, 2, value.ok_or_else(|| self.overflow())?)?;
+            }
+            Opcode::Multiply => {
+                let value = self
+                    .parameter(&instruction, 0)?
+                    .checked_mul(self.parameter(&instruction, 1)?);
+                self.write(&instruction, 2, value.ok_or_else(|| self.overflow())?)?;
+            }
+            Opcode::Input => {
+                // Check the target first, so a failing write does not lose the input value
+                self.address(&instruction, 0)?;
+                let Some(value) = self.input.pop_front() else {
+                    return Ok(Some(RunState::NeedsInput));
+                };
+                self.write(&instruction, 0, value)?;
+                if let Some(detector) = &mut self.loop_detector {
+                    detector.reset();
+                }
+            }
+            Opcode::Output => {
+                let value = self.parameter(&instruction, 0)?;
+                self.output.push(value);
+                state = Some(RunState::Output(value));
+                if let Some(detector) = &mut self.loop_detector {
+                    detector.reset();
+                }
+            }
+            Opcode::JumpIfTrue => {
+                if self.parameter(&instruction, 0)? != 0 {
+                    next = self.jump_target(&instruction)?;
+                }
+            }
+            Opcode::JumpIfFalse => {
+                if self.parameter(&instruction, 0)? == 0 {
+                    next = self.jump_target(&instruction)?;
+                }
+            }
+            Opcode::LessThan => {
+                let value = self.parameter(&instruction, 0)? < self.parameter(&instruction, 1)?;
+                self.write(&instruction, 2, value as i64)?;
+            }
+            Opcode::Equals => {
+                let value = self.parameter(&instruction, 0)? == self.parameter(&instruction, 1)?;
+                self.write(&instruction, 2, value as i64)?;
+            }
+            Opcode::AdjustRelativeBase => {
+                let relative_base = self
+                    .relative_base
+                    .checked_add(self.parameter(&instruction, 0)?);
+                self.relative_base = relative_base.ok_or_else(|| self.overflow())?;
+            }
+            Opcode::Halt => {
+                self.record(&instruction, accesses, None);
+                return Ok(Some(RunState::Halted));
+            }
+        }
+
+        let jumped = next != self.instruction_pointer + instruction.length();
+        if jumped {
+            if let Some(detector) = &mut self.loop_detector {
+                if detector.jump(next, self.relative_base, &self.memory) {
+                    let instruction_pointer = self.instruction_pointer;
+                    return Err(IntcodeError::InfiniteLoop {
+                        instruction_pointer,
+                        instruction: self.get(instruction_pointer),
+                    });
+                }
+            }
+        }
+
+        self.record(&instruction, accesses, jumped.then_some(next));
+        self.instruction_pointer = next;
+        self.executed += 1;
+        if let Some(budget) = &mut self.budget {
+            *budget -= 1;
+        }
+        Ok(state)
+    }
+
+    /// The addresses the current instruction reads and the address it writes, for the profiler.
+    fn accesses(&self, instruction: &Instruction) -> (Vec<usize>, Option<usize>) {
+        let count = instruction.opcode.parameter_count();
+        let write = instruction
+            .opcode
+            .writes()
+            .then(|| self.address(instruction, count - 1).ok())
+            .flatten();
+        let reads = (0..count - write.is_some() as usize)
+            .filter(|&index| instruction.modes[index] != Mode::Immediate)
+            .filter_map(|index| self.address(instruction, index).ok())
+            .collect();
+        (reads, write)
+    }
+
+    /// Adds the executed instruction to the profile, if profiling.
+    fn record(
+        &mut self,
+        instruction: &Instruction,
+        accesses: Option<(Vec<usize>, Option<usize>)>,
+        jump: Option<usize>,
+    ) {
+        if let (Some(profile), Some((reads, write))) = (&mut self.profile, accesses) {
+            profile.record(
+                self.instruction_pointer,
+                instruction.opcode,
+                &reads,
+                write,
+                jump,
+            );
+        }
+    }
+
+    /// Restores the parts of a saved session that can not be set through the public interface.
+    pub(crate) fn resume_session(
+        &mut self,
+        instruction_pointer: usize,
+        output: Vec<i64>,
+        executed: u64,
+    ) {
+        self.instruction_pointer = instruction_pointer;
+        self.output = output;
+        self.executed = executed;
+    }
+
+    /// The amount of instructions that may still be executed, for the compiled tier.
+    pub(crate) fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    /// Whether infinite loops are detected, which needs to see every jump.
+    pub(crate) fn detects_loops(&self) -> bool {
+        self.loop_detector.is_some()
+    }
+
+    /// Moves to `instruction_pointer` after `instructions` were executed outside of [`IntCodeComputer::step`].
+    pub(crate) fn advance(&mut self, instruction_pointer: usize, instructions: u64) {
+        self.instruction_pointer = instruction_pointer;
+        self.executed += instructions;
+        if let Some(budget) = &mut self.budget {
+            *budget -= instructions;
+        }
+    }
+
+    /// Decodes the instruction at the instruction pointer.
+    fn decode(&self) -> Result<Instruction, IntcodeError> {
+        let instruction = self.get(self.instruction_pointer);
+        Instruction::decode(instruction).ok_or_else(|| {
+            match Opcode::from_instruction(instruction) {
+                Some(_) => IntcodeError::InvalidMode {
+                    instruction_pointer: self.instruction_pointer,
+                    instruction,
+                },
+                None => IntcodeError::UnknownOpcode {
+                    instruction_pointer: self.instruction_pointer,
+                    instruction,
+                },
+            }
+        })
+    }
+
+    /// Raw value of the parameter at `index` of the current instruction.
+    fn raw_parameter(&self, index: usize) -> i64 {
+        self.get(self.instruction_pointer + 1 + index)
+    }
+
+    /// Resolves the parameter at `index` according to its mode.
+    fn parameter(&self, instruction: &Instruction, index: usize) -> Result<i64, IntcodeError> {
+        match instruction.modes[index] {
+            Mode::Immediate => Ok(self.raw_parameter(index)),
+            Mode::Position | Mode::Relative => Ok(self.get(self.address(instruction, index)?)),
+        }
+    }
+
+    /// Writes `value` to the address given by the parameter at `index`.
+    fn write(
+        &mut self,
+        instruction: &Instruction,
+        index: usize,
+        value: i64,
+    ) -> Result<(), IntcodeError> {
+        let address = self.address(instruction, index)?;
+        self.set(address, value);
+        Ok(())
+    }
+
+    /// The address a position or relative parameter at `index` points to.
+    fn address(&self, instruction: &Instruction, index: usize) -> Result<usize, IntcodeError> {
+        let instruction_pointer = self.instruction_pointer;
+        let raw = self.raw_parameter(index);
+        let address = match instruction.modes[index] {
+            Mode::Position => raw,
+            Mode::Relative => self
+                .relative_base
+                .checked_add(raw)
+                .ok_or_else(|| self.overflow())?,
+            Mode::Immediate => {
+                return Err(IntcodeError::ImmediateWrite {
+                    instruction_pointer,
+                    instruction: self.get(instruction_pointer),
+                })
+            }
+        };
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress {
+                instruction_pointer,
+                instruction: self.get(instruction_pointer),
+                address,
+            });
+        }
+        Ok(address as usize)
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        let instruction_pointer = self.instruction_pointer;
+        IntcodeError::Overflow {
+            instruction_pointer,
+            instruction: self.get(instruction_pointer),
+        }
+    }
+
+    /// The target of a jump instruction, its second parameter.
+    fn jump_target(&self, instruction: &Instruction) -> Result<usize, IntcodeError> {
+        let target = self.parameter(instruction, 1)?;
+        if target < 0 {
+            let instruction_pointer = self.instruction_pointer;
+            return Err(IntcodeError::NegativeJump {
+                instruction_pointer,
+                instruction: self.get(instruction_pointer),
+                target,
+            });
+        }
+        Ok(target as usize)
+    }
+}
+
+impl Clone for IntCodeComputer {
+    /// Clones start with an empty profile, so collected profiles count every instruction once.
+    fn clone(&self) -> Self {
+        Self {
+            memory: self.memory.clone(),
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            budget: self.budget,
+            executed: self.executed,
+            loop_detector: self.loop_detector.clone(),
+            pages: self.pages.clone(),
+            profile: self
+                .profile
+                .as_ref()
+                .map(|profile| Box::new(profile.fork())),
+        }
+    }
+}
+
+impl Drop for IntCodeComputer {
+    /// Hands the profile over to be reported when profiling through the environment.
+    fn drop(&mut self) {
+        if let Some(profile) = &self.profile {
+            if profile::enabled() {
+                profile::collect(profile);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patched_memory() {
+        let mut computer = IntCodeComputer::new("1,0,0,0,99");
+        computer.set(1, 4);
+        computer.run().unwrap();
+        assert_eq!(computer.get(0), 100);
+    }
+
+    #[test]
+    fn test_input_output() {
+        let mut computer = IntCodeComputer::new("3,0,4,0,99");
+        computer.push_input(42);
+        assert_eq!(computer.run().unwrap(), &[42]);
+    }
+
+    #[test]
+    fn test_parameter_modes() {
+        let mut computer = IntCodeComputer::new("1002,4,3,4,33");
+        computer.run().unwrap();
+        assert_eq!(computer.get(4), 99);
+    }
+
+    #[test]
+    fn test_quine() {
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut computer = IntCodeComputer::new(program);
+        let expected = program
+            .split(',')
+            .map(|value| value.parse::<i64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(computer.run().unwrap(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let mut computer = IntCodeComputer::new("1102,34915192,34915192,7,4,7,99,0");
+        assert_eq!(computer.run().unwrap()[0].to_string().len(), 16);
+
+        let mut computer = IntCodeComputer::new("104,1125899906842624,99");
+        assert_eq!(computer.run().unwrap(), &[1125899906842624]);
+    }
+
+    #[test]
+    fn test_memory_grows() {
+        let mut computer = IntCodeComputer::new("109,2000,21101,3,4,7,204,7,99");
+        assert_eq!(computer.run().unwrap(), &[7]);
+        assert_eq!(computer.get(2007), 7);
+        assert_eq!(computer.relative_base(), 2000);
+    }
+
+    #[test]
+    fn test_far_memory() {
+        // Writes and outputs a cell a trillion cells past the program
+        let program = "109,1000000000000,21101,3,4,7,204,7,99";
+        for kind in [MemoryKind::Auto, MemoryKind::Sparse] {
+            let mut computer = IntCodeComputer::new(program);
+            computer.set_memory_kind(kind);
+            let start = computer.snapshot();
+            assert_eq!(computer.run().unwrap(), &[7]);
+            assert!(computer.memory().is_sparse());
+            assert_eq!(computer.memory().len(), 1_000_000_000_008);
+            assert_eq!(
+                computer.memory().cells().last(),
+                Some(&(1_000_000_000_007, 7))
+            );
+
+            let end = computer.snapshot();
+            computer.restore(&start);
+            assert_eq!(computer.get(1_000_000_000_007), 0);
+            assert_eq!(computer.memory().len(), 9);
+            computer.restore(&end);
+            assert_eq!(computer.get(1_000_000_000_007), 7);
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut computer = IntCodeComputer::new("3,9,1001,9,1,9,4,9,99,0");
+        assert_eq!(computer.resume(), Ok(RunState::NeedsInput));
+        assert_eq!(computer.resume(), Ok(RunState::NeedsInput));
+
+        computer.push_input(41);
+        assert_eq!(computer.resume(), Ok(RunState::Output(42)));
+        assert_eq!(computer.resume(), Ok(RunState::Halted));
+        assert_eq!(computer.resume(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut computer = IntCodeComputer::new("1,0,0,0,98");
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::UnknownOpcode {
+                instruction_pointer: 4,
+                instruction: 98
+            })
+        );
+
+        let mut computer = IntCodeComputer::new("3,0,3,0,99");
+        computer.push_input(1);
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::InputExhausted {
+                instruction_pointer: 2,
+                instruction: 3
+            })
+        );
+
+        let mut computer = IntCodeComputer::new("1101,1,1,-1,99");
+        let error = computer.run().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Intcode: negative address -1 in instruction 1101 at 0000"
+        );
+
+        let mut computer = IntCodeComputer::new("1105,1,-4,99");
+        assert!(matches!(
+            computer.run(),
+            Err(IntcodeError::NegativeJump { target: -4, .. })
+        ));
+
+        let mut computer = IntCodeComputer::new("11101,1,1,0,99");
+        assert!(matches!(
+            computer.step(),
+            Err(IntcodeError::ImmediateWrite { .. })
+        ));
+        assert_eq!(computer.instruction_pointer(), 0);
+
+        assert_eq!(
+            IntCodeComputer::parse("1,0,x,0,99").unwrap_err(),
+            IntcodeError::Parse { index: 2 }
+        );
+        assert_eq!(
+            IntCodeComputer::parse("1,0,,0").unwrap_err().to_string(),
+            "Intcode: value 2 of the program is not an integer"
+        );
+
+        let mut computer = IntCodeComputer::new("1102,9223372036854775807,2,0,99");
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::Overflow {
+                instruction_pointer: 0,
+                instruction: 1102
+            })
+        );
+        assert_eq!(computer.get(0), 1102);
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut computer = IntCodeComputer::new("1105,1,0");
+        computer.set_budget(Some(1000));
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::BudgetExhausted {
+                instruction_pointer: 0,
+                instruction: 1105
+            })
+        );
+        assert_eq!(computer.executed(), 1000);
+    }
+
+    #[test]
+    fn test_infinite_loop() {
+        // Counts [14] up to 3, then jumps to itself forever
+        let mut computer =
+            IntCodeComputer::new("1001,14,1,14,1007,14,3,15,1005,15,0,1105,1,11,0,0");
+        computer.detect_loops(true);
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::InfiniteLoop {
+                instruction_pointer: 11,
+                instruction: 1105
+            })
+        );
+        assert_eq!(computer.get(14), 3);
+
+        // Keeps counting and printing, which is not a provable loop
+        let mut computer = IntCodeComputer::new("1001,9,1,9,4,9,1105,1,0,0");
+        computer.detect_loops(true);
+        computer.set_budget(Some(1000));
+        assert!(matches!(
+            computer.run(),
+            Err(IntcodeError::BudgetExhausted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let mut computer = IntCodeComputer::new("3,1000,4,1000,1105,1,0");
+        let start = computer.snapshot();
+
+        computer.push_input(7);
+        assert_eq!(computer.resume(), Ok(RunState::Output(7)));
+        let branch = computer.snapshot();
+        assert_eq!(branch.get(1000), 7);
+        assert_eq!(branch.shared_pages(&start), 1);
+
+        computer.restore(&start);
+        assert_eq!(computer.get(1000), 0);
+        assert_eq!(computer.memory().len(), 7);
+        assert_eq!(computer.output(), &[]);
+
+        computer.restore(&branch);
+        computer.push_input(8);
+        assert_eq!(computer.resume(), Ok(RunState::Output(8)));
+        assert_eq!(computer.output(), &[7, 8]);
+    }
+}
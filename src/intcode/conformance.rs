@@ -0,0 +1,268 @@
+//! Every example program from the puzzle texts of days 2, 5 and 9, run on every variant of the computer.
+use crate::intcode::compiled::CompiledComputer;
+use crate::intcode::io::IterInput;
+use crate::intcode::symbolic::{Expr, SymbolicComputer, SymbolicError};
+use crate::intcode::{IntCodeComputer, IntcodeError, MemoryKind, RunState};
+
+/// An example program with its input and the expected output and memory.
+struct Example {
+    program: &'static str,
+    input: &'static [i64],
+    output: &'static [i64],
+    /// The expected start of the memory after the program halted.
+    memory: &'static [i64],
+}
+
+const fn example(
+    program: &'static str,
+    input: &'static [i64],
+    output: &'static [i64],
+    memory: &'static [i64],
+) -> Example {
+    Example {
+        program,
+        input,
+        output,
+        memory,
+    }
+}
+
+const EQUAL_TO_8_POSITION: &str = "3,9,8,9,10,9,4,9,99,-1,8";
+const LESS_THAN_8_POSITION: &str = "3,9,7,9,10,9,4,9,99,-1,8";
+const EQUAL_TO_8_IMMEDIATE: &str = "3,3,1108,-1,8,3,4,3,99";
+const LESS_THAN_8_IMMEDIATE: &str = "3,3,1107,-1,8,3,4,3,99";
+const JUMP_POSITION: &str = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+const JUMP_IMMEDIATE: &str = "3,3,1105,-1,9,1101,0,0,12,4,12,99,1";
+const COMPARE_TO_8: &str =
+    "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,\
+                            1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+const EXAMPLES: &[Example] = &[
+    // Day 2
+    example(
+        "1,9,10,3,2,3,11,0,99,30,40,50",
+        &[],
+        &[],
+        &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50],
+    ),
+    example("1,0,0,0,99", &[], &[], &[2, 0, 0, 0, 99]),
+    example("2,3,0,3,99", &[], &[], &[2, 3, 0, 6, 99]),
+    example("2,4,4,5,99,0", &[], &[], &[2, 4, 4, 5, 99, 9801]),
+    example(
+        "1,1,1,4,99,5,6,0,99",
+        &[],
+        &[],
+        &[30, 1, 1, 4, 2, 5, 6, 0, 99],
+    ),
+    // Day 5
+    example("3,0,4,0,99", &[42], &[42], &[42, 0, 4, 0, 99]),
+    example("1002,4,3,4,33", &[], &[], &[1002, 4, 3, 4, 99]),
+    example("1101,100,-1,4,0", &[], &[], &[1101, 100, -1, 4, 99]),
+    example(EQUAL_TO_8_POSITION, &[8], &[1], &[]),
+    example(EQUAL_TO_8_POSITION, &[7], &[0], &[]),
+    example(LESS_THAN_8_POSITION, &[7], &[1], &[]),
+    example(LESS_THAN_8_POSITION, &[8], &[0], &[]),
+    example(EQUAL_TO_8_IMMEDIATE, &[8], &[1], &[]),
+    example(EQUAL_TO_8_IMMEDIATE, &[9], &[0], &[]),
+    example(LESS_THAN_8_IMMEDIATE, &[-3], &[1], &[]),
+    example(LESS_THAN_8_IMMEDIATE, &[9], &[0], &[]),
+    example(JUMP_POSITION, &[0], &[0], &[]),
+    example(JUMP_POSITION, &[5], &[1], &[]),
+    example(JUMP_IMMEDIATE, &[0], &[0], &[]),
+    example(JUMP_IMMEDIATE, &[-5], &[1], &[]),
+    example(COMPARE_TO_8, &[7], &[999], &[]),
+    example(COMPARE_TO_8, &[8], &[1000], &[]),
+    example(COMPARE_TO_8, &[9], &[1001], &[]),
+    // Day 9
+    example(
+        QUINE,
+        &[],
+        &[
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ],
+        &[],
+    ),
+    example(
+        "1102,34915192,34915192,7,4,7,99,0",
+        &[],
+        &[1219070632396864],
+        &[],
+    ),
+    example("104,1125899906842624,99", &[], &[1125899906842624], &[]),
+];
+
+/// The output and final memory of a run.
+type Outcome = Result<(Vec<i64>, Vec<i64>), IntcodeError>;
+
+/// A way to run a program on its input.
+type Variant = fn(&str, &[i64]) -> Outcome;
+
+/// Every way to run a program, they all have to agree with [`IntCodeComputer::run`].
+const VARIANTS: &[(&str, Variant)] = &[
+    ("run", run),
+    ("run_with", run_with),
+    ("sparse", sparse),
+    ("resume", resume),
+    ("step", step),
+    ("restore", restore),
+    ("compiled", compiled),
+    ("symbolic", symbolic),
+];
+
+fn computer(program: &str, input: &[i64]) -> IntCodeComputer {
+    let mut computer = IntCodeComputer::new(program);
+    input.iter().for_each(|&value| computer.push_input(value));
+    computer
+}
+
+fn run(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = computer(program, input);
+    let output = computer.run()?.to_vec();
+    Ok((output, computer.memory().to_vec()))
+}
+
+fn run_with(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = IntCodeComputer::new(program);
+    let mut output = vec![];
+    computer.run_with(IterInput(input.iter().copied()), &mut output)?;
+    Ok((output, computer.memory().to_vec()))
+}
+
+fn sparse(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = computer(program, input);
+    computer.set_memory_kind(MemoryKind::Sparse);
+    let output = computer.run()?.to_vec();
+    Ok((output, computer.memory().to_vec()))
+}
+
+/// Hands over the input one value at a time, only when asked for.
+fn resume(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = IntCodeComputer::new(program);
+    let mut input = input.iter();
+    let mut output = vec![];
+    loop {
+        match computer.resume()? {
+            RunState::NeedsInput => match input.next() {
+                Some(&value) => computer.push_input(value),
+                None => return Err(computer.run().unwrap_err()),
+            },
+            RunState::Output(value) => output.push(value),
+            RunState::Halted => return Ok((output, computer.memory().to_vec())),
+        }
+    }
+}
+
+fn step(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = computer(program, input);
+    loop {
+        match computer.step()? {
+            Some(RunState::Halted) => {
+                return Ok((computer.output().to_vec(), computer.memory().to_vec()))
+            }
+            Some(RunState::NeedsInput) => return Err(computer.run().unwrap_err()),
+            _ => {}
+        }
+    }
+}
+
+/// Runs to the end, restores the starting snapshot and runs again.
+fn restore(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = computer(program, input);
+    let start = computer.snapshot();
+    let _ = computer.run();
+    computer.restore(&start);
+    let output = computer.run()?.to_vec();
+    Ok((output, computer.memory().to_vec()))
+}
+
+fn compiled(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = CompiledComputer::new(computer(program, input));
+    let output = computer.run()?.to_vec();
+    Ok((output, computer.computer().memory().to_vec()))
+}
+
+/// Without any symbols the symbolic computer computes the concrete results.
+fn symbolic(program: &str, input: &[i64]) -> Outcome {
+    let mut computer = SymbolicComputer::new(&computer(program, input));
+    computer.run().map_err(|error| match error {
+        SymbolicError::Intcode(error) => error,
+        SymbolicError::DependsOnSymbol { .. } => unreachable!("no symbols were used"),
+    })?;
+    let constant = |expr: &Expr| expr.constant().expect("no symbols were used");
+    let mut memory = vec![0; computer.len()];
+    computer
+        .cells()
+        .iter()
+        .for_each(|(address, expr)| memory[*address] = constant(expr));
+    Ok((computer.output().iter().map(constant).collect(), memory))
+}
+
+#[test]
+fn test_examples() {
+    for example in EXAMPLES {
+        let (output, memory) = run(example.program, example.input).unwrap();
+        assert_eq!(output, example.output, "{}", example.program);
+        assert!(
+            memory.starts_with(example.memory),
+            "{}: {memory:?}",
+            example.program
+        );
+    }
+}
+
+#[test]
+fn test_relative_base() {
+    // From the day 9 text: with relative base 2000, 109,19 moves it to 2019 and 204,-34 outputs address 1985
+    let mut computer = IntCodeComputer::new("109,19,204,-34,99");
+    computer.set_relative_base(2000);
+    computer.set(1985, 42);
+    assert_eq!(computer.run(), Ok(&[42][..]));
+    assert_eq!(computer.relative_base(), 2019);
+}
+
+#[test]
+fn test_variants_agree() {
+    for example in EXAMPLES {
+        let expected = run(example.program, example.input);
+        for (name, variant) in VARIANTS {
+            assert_eq!(
+                variant(example.program, example.input),
+                expected,
+                "{name}: {}",
+                example.program
+            );
+        }
+    }
+}
+
+#[test]
+fn test_variants_agree_on_errors() {
+    let failing: &[(&str, &[i64])] = &[
+        // Unknown opcode, invalid mode, immediate write, negative address and negative jump
+        ("1,0,0,0,98", &[]),
+        ("1301,0,0,0,99", &[]),
+        ("11101,1,1,1,99", &[]),
+        ("1,-1,0,0,99", &[]),
+        ("1105,1,-3,99", &[]),
+        // A jump far beyond the program
+        ("1105,1,100000000000", &[]),
+        // Overflowing multiplication, addition, relative base and relative address
+        ("1102,9223372036854775807,2,0,99", &[]),
+        ("1101,9223372036854775807,1,0,99", &[]),
+        ("109,9223372036854775807,109,1,99", &[]),
+        ("109,9223372036854775807,204,1,99", &[]),
+        ("109,9223372036854775807,203,1,99", &[1]),
+        ("109,9223372036854775807,21101,1,1,1,99", &[]),
+        // Input runs out
+        (EQUAL_TO_8_POSITION, &[]),
+        ("3,0,3,0,99", &[1]),
+    ];
+    for (program, input) in failing {
+        let expected = run(program, input);
+        assert!(expected.is_err(), "{program}");
+        for (name, variant) in VARIANTS {
+            assert_eq!(variant(program, input), expected, "{name}: {program}");
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use crate::intcode::disassembler::disassemble_at;
+use crate::intcode::instruction::{Instruction, Opcode};
+use crate::intcode::{IntCodeComputer, RunState};
+
+/// The amount of executed instructions kept for the `history` command.
+const HISTORY_SIZE: usize = 256;
+/// The most cells `mem` shows at once.
+const MEMORY_CELLS: usize = 1000;
+/// The most instructions `continue` executes before handing control back, so a program that loops
+/// forever can still be inspected.
+const CONTINUE_LIMIT: usize = 1_000_000;
+
+pub const HELP: &str = "\
+step [n]              execute n instructions (default 1), an empty line steps once
+continue              run until a breakpoint, a watchpoint, input is needed, the program halts or 1000000 instructions ran
+break <addr|OP>       break before the instruction at an address or on every instruction with a mnemonic
+delete <addr|OP>      remove a breakpoint
+watch <addr>          stop whenever the value at an address changes
+unwatch <addr>        remove a watchpoint
+mem <addr> [n]        show n memory cells starting at an address (default 1, at most 1000)
+set <addr> <value>    overwrite a memory cell
+rb [value]            show or set the relative base
+input <v> [<v>...]    queue input values, separated by spaces or commas
+list [addr] [n]       disassemble n instructions from an address (default the instruction pointer)
+history [n]           show the last n executed instructions (default 10)
+info                  show the instruction pointer, relative base, breakpoints and queued input
+save <file>           save the computer as a JSON session, resume it by passing the file instead of a program
+quit                  leave the debugger";
+
+/// An interactive debugger around an [`IntCodeComputer`], driven by text commands (see [`HELP`]).
+pub struct Debugger {
+    computer: IntCodeComputer,
+    breakpoints: BTreeSet<usize>,
+    opcode_breakpoints: HashSet<Opcode>,
+    /// Watched addresses together with the value they had when last checked.
+    watchpoints: BTreeMap<usize, i64>,
+    history: VecDeque<String>,
+    /// The address the last breakpoint stopped at, `continue` executes that instruction instead of stopping again.
+    stopped_at: Option<usize>,
+}
+
+impl Debugger {
+    pub fn new(computer: IntCodeComputer) -> Self {
+        Self {
+            computer,
+            breakpoints: BTreeSet::new(),
+            opcode_breakpoints: HashSet::new(),
+            watchpoints: BTreeMap::new(),
+            history: VecDeque::with_capacity(HISTORY_SIZE),
+            stopped_at: None,
+        }
+    }
+
+    pub fn computer(&self) -> &IntCodeComputer {
+        &self.computer
+    }
+
+    /// Executes a single command line and returns the text to show to the user.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("step");
+        let arguments = words.collect::<Vec<_>>();
+
+        match (command, arguments.as_slice()) {
+            ("step" | "s", []) => Ok(self.step(1)),
+            ("step" | "s", [count]) => Ok(self.step(parse(count)?)),
+            ("continue" | "c", []) => Ok(self.resume()),
+            ("break" | "b", [target]) => {
+                match parse_target(target)? {
+                    Target::Address(address) => self.breakpoints.insert(address),
+                    Target::Opcode(opcode) => self.opcode_breakpoints.insert(opcode),
+                };
+                Ok(format!("Breakpoint set on {target}"))
+            }
+            ("delete" | "d", [target]) => {
+                let removed = match parse_target(target)? {
+                    Target::Address(address) => self.breakpoints.remove(&address),
+                    Target::Opcode(opcode) => self.opcode_breakpoints.remove(&opcode),
+                };
+                if removed {
+                    Ok(format!("Breakpoint on {target} removed"))
+                } else {
+                    Err(format!("No breakpoint on {target}"))
+                }
+            }
+            ("watch" | "w", [address]) => {
+                let address = parse(address)?;
+                self.watchpoints.insert(address, self.computer.get(address));
+                Ok(format!(
+                    "Watching [{address}] = {}",
+                    self.computer.get(address)
+                ))
+            }
+            ("unwatch", [address]) => match self.watchpoints.remove(&parse(address)?) {
+                Some(_) => Ok(format!("Stopped watching [{address}]")),
+                None => Err(format!("No watchpoint on [{address}]")),
+            },
+            ("mem" | "m", [address]) => self.memory(parse(address)?, 1),
+            ("mem" | "m", [address, count]) => self.memory(parse(address)?, parse(count)?),
+            ("set", [address, value]) => {
+                let address = parse(address)?;
+                let value = parse(value)?;
+                self.computer.set(address, value);
+                // The program did not write the cell, so a watchpoint on it must not trigger on the next step
+                if let Some(last) = self.watchpoints.get_mut(&address) {
+                    *last = value;
+                }
+                self.memory(address, 1)
+            }
+            ("rb", []) => Ok(format!("rb = {}", self.computer.relative_base())),
+            ("rb", [value]) => {
+                self.computer.set_relative_base(parse(value)?);
+                Ok(format!("rb = {}", self.computer.relative_base()))
+            }
+            ("input" | "i", values) if !values.is_empty() => {
+                for value in values
+                    .iter()
+                    .flat_map(|value| value.split(','))
+                    .filter(|value| !value.is_empty())
+                {
+                    self.computer.push_input(parse(value)?);
+                }
+                Ok(format!(
+                    "Queued input: {:?}",
+                    self.computer.pending_input().collect::<Vec<_>>()
+                ))
+            }
+            ("list" | "l", []) => Ok(self.list(self.computer.instruction_pointer(), 10)),
+            ("list" | "l", [address]) => Ok(self.list(parse(address)?, 10)),
+            ("list" | "l", [address, count]) => Ok(self.list(parse(address)?, parse(count)?)),
+            ("history" | "h", []) => Ok(self.history(10)),
+            ("history" | "h", [count]) => Ok(self.history(parse(count)?)),
+            ("info", []) => Ok(self.info()),
+            ("save", [path]) => match self.computer.save(path) {
+                Ok(()) => Ok(format!("Session saved to \"{path}\"")),
+                Err(e) => Err(format!("Failed to save session: {e}")),
+            },
+            ("help", []) => Ok(HELP.to_string()),
+            _ => Err(format!(
+                "Unknown command \"{}\", type `help` for a list of commands",
+                line.trim()
+            )),
+        }
+    }
+
+    /// Executes up to `count` instructions, stopping early on anything that hands control back.
+    fn step(&mut self, count: usize) -> String {
+        let mut report = vec![];
+        for _ in 0..count {
+            let (lines, stop) = self.execute_instruction();
+            report.extend(lines);
+            if stop {
+                break;
+            }
+        }
+        report.push(self.current());
+        report.join("\n")
+    }
+
+    /// Runs until a breakpoint or watchpoint triggers, input is needed, the program halts or
+    /// [`CONTINUE_LIMIT`] instructions ran.
+    fn resume(&mut self) -> String {
+        let mut report = vec![];
+        let stopped_at = self.stopped_at.take();
+        for executed in 0..=CONTINUE_LIMIT {
+            let address = self.computer.instruction_pointer();
+            if executed > 0 || stopped_at != Some(address) {
+                if let Some(breakpoint) = self.breakpoint(address) {
+                    report.push(breakpoint);
+                    self.stopped_at = Some(address);
+                    break;
+                }
+            }
+            if executed == CONTINUE_LIMIT {
+                report.push(format!("Paused after {CONTINUE_LIMIT} instructions"));
+                break;
+            }
+
+            let (lines, stop) = self.execute_instruction();
+            report.extend(lines);
+            if stop {
+                break;
+            }
+        }
+        report.push(self.current());
+        report.join("\n")
+    }
+
+    /// Describes the breakpoint on the instruction at `address`, if there is one.
+    fn breakpoint(&self, address: usize) -> Option<String> {
+        if self.breakpoints.contains(&address) {
+            return Some(format!("Breakpoint at {address:04}"));
+        }
+        let instruction = Instruction::decode(self.computer.get(address))?;
+        self.opcode_breakpoints
+            .contains(&instruction.opcode)
+            .then(|| format!("Breakpoint on {}", instruction.opcode.mnemonic()))
+    }
+
+    /// Executes one instruction, returns the lines to report and whether execution should stop.
+    fn execute_instruction(&mut self) -> (Vec<String>, bool) {
+        let address = self.computer.instruction_pointer();
+        let text = self.describe(address);
+
+        let mut lines = vec![];
+        let state = match self.computer.step() {
+            Ok(state) => state,
+            Err(e) => return (vec![e.to_string()], true),
+        };
+        match state {
+            Some(RunState::NeedsInput) => {
+                return (
+                    vec!["Waiting for input, queue some with `input`".to_string()],
+                    true,
+                )
+            }
+            Some(RunState::Halted) => return (vec!["Halted".to_string()], true),
+            Some(RunState::Output(value)) => lines.push(format!("Output: {value}")),
+            None => {}
+        }
+
+        if self.history.len() == HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(text);
+
+        let mut triggered = false;
+        for (&watched, last) in self.watchpoints.iter_mut() {
+            let value = self.computer.get(watched);
+            if value != *last {
+                lines.push(format!("Watchpoint [{watched}]: {last} -> {value}"));
+                *last = value;
+                triggered = true;
+            }
+        }
+
+        (lines, triggered)
+    }
+
+    fn describe(&self, address: usize) -> String {
+        match disassemble_at(self.computer.memory(), address) {
+            Some(line) => line.to_string(),
+            None => format!("{address:04}: DATA {}", self.computer.get(address)),
+        }
+    }
+
+    fn current(&self) -> String {
+        format!("=> {}", self.describe(self.computer.instruction_pointer()))
+    }
+
+    fn memory(&self, address: usize, count: usize) -> Result<String, String> {
+        if count > MEMORY_CELLS {
+            return Err(format!("Showing at most {MEMORY_CELLS} cells at once"));
+        }
+        let end = address
+            .checked_add(count)
+            .ok_or_else(|| format!("{count} cells from [{address}] are past the last address"))?;
+        Ok((address..end)
+            .map(|address| format!("[{address}] = {}", self.computer.get(address)))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Lists `count` lines from `address`, skipping the pages of sparse memory that were never written.
+    fn list(&self, mut address: usize, count: usize) -> String {
+        let memory = self.computer.memory();
+        let mut lines = vec![];
+        for _ in 0..count {
+            let Some(next) = memory.next_mapped(address) else {
+                break;
+            };
+            address = next;
+            let marker = if address == self.computer.instruction_pointer() {
+                "=>"
+            } else {
+                "  "
+            };
+            lines.push(format!("{marker} {}", self.describe(address)));
+            address +=
+                disassemble_at(self.computer.memory(), address).map_or(1, |line| line.length);
+        }
+        lines.join("\n")
+    }
+
+    fn history(&self, count: usize) -> String {
+        let skip = self.history.len().saturating_sub(count);
+        self.history
+            .iter()
+            .skip(skip)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn info(&self) -> String {
+        let mut breakpoints = self
+            .breakpoints
+            .iter()
+            .map(|address| format!("{address:04}"))
+            .collect::<Vec<_>>();
+        breakpoints.extend(
+            self.opcode_breakpoints
+                .iter()
+                .map(|opcode| opcode.mnemonic().to_string()),
+        );
+        format!(
+            "ip = {}\nrb = {}\nbreakpoints: {}\nwatchpoints: {:?}\ninput: {:?}\noutput: {:?}",
+            self.computer.instruction_pointer(),
+            self.computer.relative_base(),
+            breakpoints.join(", "),
+            self.watchpoints.keys().collect::<Vec<_>>(),
+            self.computer.pending_input().collect::<Vec<_>>(),
+            self.computer.output(),
+        )
+    }
+}
+
+enum Target {
+    Address(usize),
+    Opcode(Opcode),
+}
+
+fn parse_target(text: &str) -> Result<Target, String> {
+    match text.parse::<usize>() {
+        Ok(address) => Ok(Target::Address(address)),
+        Err(_) => Opcode::from_mnemonic(text)
+            .map(Target::Opcode)
+            .ok_or_else(|| format!("Unknown address or mnemonic \"{text}\"")),
+    }
+}
+
+fn parse<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse::<T>()
+        .map_err(|_| format!("Invalid number \"{text}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debugger() -> Debugger {
+        Debugger::new(IntCodeComputer::new("3,9,1001,9,1,9,4,9,99,0"))
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = debugger();
+        debugger.execute("input 41").unwrap();
+        debugger.execute("break OUT").unwrap();
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            "Breakpoint on OUT\n=> 0006: OUT [9]"
+        );
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "Output: 42\nHalted\n=> 0008: HLT"
+        );
+        assert_eq!(
+            debugger.execute("history 2").unwrap(),
+            "0002: ADD [9], #1 -> [9]\n0006: OUT [9]"
+        );
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut debugger = debugger();
+        debugger.execute("watch 9").unwrap();
+        assert_eq!(
+            debugger.execute("").unwrap(),
+            "Waiting for input, queue some with `input`\n=> 0000: IN -> [9]"
+        );
+        debugger.execute("input 1,2").unwrap();
+        assert_eq!(
+            debugger.execute("c").unwrap(),
+            "Watchpoint [9]: 0 -> 1\n=> 0002: ADD [9], #1 -> [9]"
+        );
+        debugger.execute("set 9 10").unwrap();
+        debugger.execute("rb 5").unwrap();
+        assert_eq!(debugger.execute("mem 9").unwrap(), "[9] = 10");
+        assert_eq!(debugger.computer().relative_base(), 5);
+        assert!(debugger.execute("jump 3").is_err());
+        assert!(debugger.execute(&format!("mem 2 {}", usize::MAX)).is_err());
+        assert!(debugger.execute("mem 0 1000000000").is_err());
+    }
+
+    #[test]
+    fn test_input() {
+        let mut debugger = debugger();
+        assert_eq!(
+            debugger.execute("input 1 2").unwrap(),
+            "Queued input: [1, 2]"
+        );
+        assert_eq!(
+            debugger.execute("i 3, 4,5").unwrap(),
+            "Queued input: [1, 2, 3, 4, 5]"
+        );
+        assert!(debugger.execute("input 1 x").is_err());
+    }
+
+    #[test]
+    fn test_breakpoint_on_current_instruction() {
+        let mut debugger = debugger();
+        debugger.execute("break IN").unwrap();
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            "Breakpoint on IN\n=> 0000: IN -> [9]"
+        );
+        debugger.execute("input 1").unwrap();
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            "Output: 2\nHalted\n=> 0008: HLT"
+        );
+    }
+
+    #[test]
+    fn test_continue_limit() {
+        let mut debugger = Debugger::new(IntCodeComputer::new("1105,1,0"));
+        assert_eq!(
+            debugger.execute("continue").unwrap(),
+            format!("Paused after {CONTINUE_LIMIT} instructions\n=> 0000: JNZ #1, #0")
+        );
+        assert_eq!(debugger.computer().executed(), CONTINUE_LIMIT as u64);
+    }
+
+    #[test]
+    fn test_set_watched_cell() {
+        let mut debugger = debugger();
+        debugger.execute("watch 9").unwrap();
+        debugger.execute("set 9 5").unwrap();
+        debugger.execute("input 1").unwrap();
+        assert_eq!(
+            debugger.execute("step").unwrap(),
+            "Watchpoint [9]: 5 -> 1\n=> 0002: ADD [9], #1 -> [9]"
+        );
+    }
+
+    #[test]
+    fn test_list_sparse_memory() {
+        let mut debugger = debugger();
+        debugger.execute("set 1000000000000 99").unwrap();
+        assert!(debugger.computer().memory().is_sparse());
+        assert_eq!(
+            debugger.execute("list 8 3").unwrap(),
+            "   0008: HLT\n   0009: DATA 0\n   0010: DATA 0"
+        );
+        assert_eq!(
+            debugger.execute("list 255 2").unwrap(),
+            "   0255: DATA 0\n   1000000000000: HLT"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::Display;
+
+use crate::intcode::instruction::{Instruction, Mode};
+use crate::intcode::memory::Memory;
+
+/// The most data cells grouped on a single `DATA` line.
+const DATA_PER_LINE: usize = 8;
+
+/// A single line of a disassembly listing, either an instruction or a run of data cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub address: usize,
+    /// The amount of memory cells covered by this line.
+    pub length: usize,
+    pub text: String,
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}: {}", self.address, self.text)
+    }
+}
+
+/// Disassembles the instruction at `address`, `None` if the cell does not hold a complete, valid instruction.
+pub fn disassemble_at(memory: &Memory, address: usize) -> Option<Line> {
+    if address >= memory.len() {
+        return None;
+    }
+    let instruction = Instruction::decode(memory.get(address))?;
+    let length = instruction.length();
+    if address + length > memory.len() {
+        return None;
+    }
+
+    let operands = (address + 1..address + length)
+        .zip(instruction.modes)
+        .map(|(parameter, mode)| operand(memory.get(parameter), mode))
+        .collect::<Vec<_>>();
+
+    let mnemonic = instruction.opcode.mnemonic();
+    let text = match (instruction.opcode.writes(), operands.split_last()) {
+        (true, Some((target, []))) => format!("{mnemonic} -> {target}"),
+        (true, Some((target, sources))) => format!("{mnemonic} {} -> {target}", sources.join(", ")),
+        _ if operands.is_empty() => mnemonic.to_string(),
+        _ => format!("{mnemonic} {}", operands.join(", ")),
+    };
+
+    Some(Line {
+        address,
+        length,
+        text,
+    })
+}
+
+/// Disassembles a whole program from address 0 onwards. Cells that do not decode to an instruction are
+/// grouped into `DATA` lines, decoding continues at the next cell. Pages of sparse memory that were never
+/// written are skipped, so memory written far beyond the program lists quickly.
+pub fn disassemble(memory: &Memory) -> Vec<Line> {
+    let mut lines: Vec<Line> = vec![];
+    let mut address = 0;
+    while let Some(next) = memory.next_mapped(address) {
+        address = next;
+        if let Some(line) = disassemble_at(memory, address) {
+            address += line.length;
+            lines.push(line);
+            continue;
+        }
+
+        // Extend the previous data line if there is room left, otherwise start a new one
+        match lines.last_mut() {
+            Some(line)
+                if line.text.starts_with("DATA")
+                    && line.length < DATA_PER_LINE
+                    && line.address + line.length == address =>
+            {
+                line.text.push_str(&format!(", {}", memory.get(address)));
+                line.length += 1;
+            }
+            _ => lines.push(Line {
+                address,
+                length: 1,
+                text: format!("DATA {}", memory.get(address)),
+            }),
+        }
+        address += 1;
+    }
+    lines
+}
+
+/// Formats a parameter according to its mode, `[12]` for positions, `#12` for immediates and `[rb+12]` for relatives.
+fn operand(value: i64, mode: Mode) -> String {
+    match mode {
+        Mode::Position => format!("[{value}]"),
+        Mode::Immediate => format!("#{value}"),
+        Mode::Relative if value < 0 => format!("[rb{value}]"),
+        Mode::Relative => format!("[rb+{value}]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::snapshot::PAGE_SIZE;
+    use crate::intcode::MemoryKind;
+
+    fn listing(memory: &[i64]) -> Vec<String> {
+        disassemble(&Memory::from(memory.to_vec()))
+            .iter()
+            .map(Line::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_instructions() {
+        let memory = [1001, 224, 5, 225, 3, 0, 204, -1, 1105, 1, 12, 99];
+        assert_eq!(
+            listing(&memory),
+            vec![
+                "0000: ADD [224], #5 -> [225]",
+                "0004: IN -> [0]",
+                "0006: OUT [rb-1]",
+                "0008: JNZ #1, #12",
+                "0011: HLT"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_data() {
+        let memory = [99, 0, 1234, 3, 1, 2];
+        assert_eq!(
+            listing(&memory),
+            vec![
+                "0000: HLT",
+                "0001: DATA 0, 1234",
+                "0003: IN -> [1]",
+                "0005: DATA 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sparse_gaps() {
+        let mut memory = Memory::new(vec![104, 1, 1234, 0, 0], MemoryKind::Sparse);
+        memory.set(1_000_000_000_000, 99);
+        memory.set(1_000_000_000_002, 0);
+        let lines = disassemble(&memory);
+        assert_eq!(
+            lines[..2].iter().map(Line::to_string).collect::<Vec<_>>(),
+            ["0000: OUT #1", "0002: DATA 1234, 0, 0, 0, 0, 0, 0, 0"]
+        );
+        assert_eq!(
+            lines[lines.len() - 2..]
+                .iter()
+                .map(Line::to_string)
+                .collect::<Vec<_>>(),
+            ["1000000000000: HLT", "1000000000001: DATA 0, 0"]
+        );
+        // Only the pages that were written are listed
+        assert!(lines.iter().all(|line| line.address < PAGE_SIZE
+            || line.address >= 1_000_000_000_000 / PAGE_SIZE * PAGE_SIZE));
+    }
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::fmt::Display;
+
+/// Everything that can go wrong while loading or executing an Intcode program.
+/// Each execution error carries the instruction pointer and the raw instruction that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntcodeError {
+    /// The last two digits of the instruction are not a known opcode.
+    UnknownOpcode {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// A parameter mode digit is not 0, 1 or 2.
+    InvalidMode {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// A parameter the instruction writes to is in immediate mode.
+    ImmediateWrite {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// A position or relative parameter points before address 0.
+    NegativeAddress {
+        instruction_pointer: usize,
+        instruction: i64,
+        address: i64,
+    },
+    /// A jump targets an address before 0.
+    NegativeJump {
+        instruction_pointer: usize,
+        instruction: i64,
+        target: i64,
+    },
+    /// The program reads input while none is queued and the caller cannot provide more.
+    InputExhausted {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// The instruction budget set with [`IntCodeComputer::set_budget`] is used up.
+    ///
+    /// [`IntCodeComputer::set_budget`]: crate::intcode::IntCodeComputer::set_budget
+    BudgetExhausted {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// The computer returned to an earlier state without input or output in between, so it never halts.
+    InfiniteLoop {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// An addition, multiplication, relative base or relative address does not fit in 64 bits.
+    Overflow {
+        instruction_pointer: usize,
+        instruction: i64,
+    },
+    /// The value at `index` of a program text is not an integer, see [`IntCodeComputer::parse`].
+    ///
+    /// [`IntCodeComputer::parse`]: crate::intcode::IntCodeComputer::parse
+    Parse { index: usize },
+}
+
+impl IntcodeError {
+    /// The address of the instruction that failed, the index of the value for [`IntcodeError::Parse`].
+    pub fn instruction_pointer(&self) -> usize {
+        match *self {
+            IntcodeError::Parse { index } => index,
+            IntcodeError::UnknownOpcode {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::InvalidMode {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::ImmediateWrite {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::NegativeAddress {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::NegativeJump {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::InputExhausted {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::BudgetExhausted {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::InfiniteLoop {
+                instruction_pointer,
+                ..
+            }
+            | IntcodeError::Overflow {
+                instruction_pointer,
+                ..
+            } => instruction_pointer,
+        }
+    }
+
+    /// The raw value of the instruction that failed, 0 for [`IntcodeError::Parse`].
+    pub fn instruction(&self) -> i64 {
+        match *self {
+            IntcodeError::Parse { .. } => 0,
+            IntcodeError::UnknownOpcode { instruction, .. }
+            | IntcodeError::InvalidMode { instruction, .. }
+            | IntcodeError::ImmediateWrite { instruction, .. }
+            | IntcodeError::NegativeAddress { instruction, .. }
+            | IntcodeError::NegativeJump { instruction, .. }
+            | IntcodeError::InputExhausted { instruction, .. }
+            | IntcodeError::BudgetExhausted { instruction, .. }
+            | IntcodeError::InfiniteLoop { instruction, .. }
+            | IntcodeError::Overflow { instruction, .. } => instruction,
+        }
+    }
+}
+
+impl Error for IntcodeError {}
+
+impl Display for IntcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            IntcodeError::Parse { index } => {
+                return write!(f, "Intcode: value {index} of the program is not an integer")
+            }
+            IntcodeError::UnknownOpcode { .. } => "unknown opcode".to_string(),
+            IntcodeError::InvalidMode { .. } => "invalid parameter mode".to_string(),
+            IntcodeError::ImmediateWrite { .. } => "write to an immediate parameter".to_string(),
+            IntcodeError::NegativeAddress { address, .. } => format!("negative address {address}"),
+            IntcodeError::NegativeJump { target, .. } => {
+                format!("jump to negative address {target}")
+            }
+            IntcodeError::InputExhausted { .. } => "input exhausted".to_string(),
+            IntcodeError::BudgetExhausted { .. } => "instruction budget exhausted".to_string(),
+            IntcodeError::InfiniteLoop { .. } => "infinite loop".to_string(),
+            IntcodeError::Overflow { .. } => "integer overflow".to_string(),
+        };
+        write!(
+            f,
+            "Intcode: {reason} in instruction {} at {:04}",
+            self.instruction(),
+            self.instruction_pointer()
+        )
+    }
+}
@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+use std::io::{stdout, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::intcode::{IntCodeComputer, IntcodeError, RunState};
+
+/// A tile position, `x` grows to the right and `y` grows downwards like the rows of the terminal.
+pub type Position = (i64, i64);
+
+/// The heading of a robot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Every direction, clockwise starting at [`Direction::Up`].
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn(self, turn: Turn) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&direction| direction == self)
+            .unwrap();
+        match turn {
+            Turn::Left => Self::ALL[(index + 3) % 4],
+            Turn::Right => Self::ALL[(index + 1) % 4],
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn(Turn::Right).turn(Turn::Right)
+    }
+
+    /// The change of position when moving one tile in this direction.
+    pub fn offset(self) -> Position {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    /// The character drawn for a robot facing this direction.
+    pub fn glyph(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// Where a robot stands and where it is heading (days 11 and 15).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pose {
+    pub position: Position,
+    pub direction: Direction,
+}
+
+impl Pose {
+    pub fn new(position: Position, direction: Direction) -> Self {
+        Self {
+            position,
+            direction,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.direction = self.direction.turn(turn);
+    }
+
+    /// The position one tile ahead, in the direction the robot is facing.
+    pub fn ahead(&self) -> Position {
+        neighbour(self.position, self.direction)
+    }
+
+    /// Moves one tile ahead.
+    pub fn advance(&mut self) {
+        self.position = self.ahead();
+    }
+}
+
+/// The position one tile away from `position` in `direction`.
+pub fn neighbour(position: Position, direction: Direction) -> Position {
+    let (dx, dy) = direction.offset();
+    (position.0 + dx, position.1 + dy)
+}
+
+/// A map that only stores the tiles that are known, so it can grow in every direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileMap<T> {
+    tiles: HashMap<Position, T>,
+}
+
+impl<T> Default for TileMap<T> {
+    fn default() -> Self {
+        Self {
+            tiles: HashMap::new(),
+        }
+    }
+}
+
+impl<T> TileMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.tiles.get(&position)
+    }
+
+    /// Places a tile, returns the tile that was there before.
+    pub fn set(&mut self, position: Position, tile: T) -> Option<T> {
+        self.tiles.insert(position, tile)
+    }
+
+    pub fn remove(&mut self, position: Position) -> Option<T> {
+        self.tiles.remove(&position)
+    }
+
+    /// The amount of known tiles, e.g. the panels painted at least once on day 11.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Every known tile, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.tiles.iter().map(|(&position, tile)| (position, tile))
+    }
+
+    /// The top left and bottom right corner of the known tiles, `None` when the map is empty.
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let mut positions = self.tiles.keys();
+        let &first = positions.next()?;
+        Some(positions.fold(
+            (first, first),
+            |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        ))
+    }
+
+    /// Draws the known tiles row by row, positions without a tile are drawn as spaces and trailing
+    /// spaces are trimmed. `glyph` picks the character of a tile.
+    pub fn render(&self, glyph: impl Fn(&T) -> char) -> String {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return String::new();
+        };
+
+        let mut text = String::new();
+        for y in min_y..=max_y {
+            let row = (min_x..=max_x)
+                .map(|x| self.get((x, y)).map_or(' ', &glyph))
+                .collect::<String>();
+            text.push_str(row.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The world an Intcode program plays in: the tiles it revealed and, for robots, where the robot is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct World<T> {
+    pub map: TileMap<T>,
+    pub robot: Option<Pose>,
+}
+
+impl<T> World<T> {
+    /// A world without a robot, e.g. the screen of the arcade cabinet of day 13.
+    pub fn new() -> Self {
+        Self {
+            map: TileMap::new(),
+            robot: None,
+        }
+    }
+
+    /// A world with a robot starting at `pose`.
+    pub fn with_robot(pose: Pose) -> Self {
+        Self {
+            map: TileMap::new(),
+            robot: Some(pose),
+        }
+    }
+
+    /// Draws the map like [`TileMap::render`], with the robot drawn on top.
+    pub fn render(&self, glyph: impl Fn(&T) -> char) -> String {
+        let mut map = TileMap::new();
+        self.map.iter().for_each(|(position, tile)| {
+            map.set(position, glyph(tile));
+        });
+        if let Some(robot) = self.robot {
+            map.set(robot.position, robot.direction.glyph());
+        }
+        map.render(|&glyph| glyph)
+    }
+}
+
+impl<T> Default for World<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The strategy of a [`Game`]: turns the output of the program into changes of the world and decides
+/// on the input whenever the program asks for one. Each of days 11, 13 and 15 is a policy.
+pub trait Policy {
+    type Tile;
+
+    /// How many output values form one message, e.g. 3 for the `x, y, tile` of the arcade cabinet.
+    const MESSAGE_LEN: usize;
+
+    /// Applies a complete message of [`Policy::MESSAGE_LEN`] values to the world.
+    fn output(&mut self, world: &mut World<Self::Tile>, message: &[i64]);
+
+    /// The next input when the program asks for one, `None` stops the game, e.g. once the
+    /// oxygen system of day 15 is found.
+    fn input(&mut self, world: &mut World<Self::Tile>) -> Option<i64>;
+
+    /// The character drawn for a tile.
+    fn glyph(&self, tile: &Self::Tile) -> char;
+
+    /// A line shown below the map when the game is displayed, e.g. the score.
+    fn status(&self) -> String {
+        String::new()
+    }
+}
+
+/// Why [`Game::run`] returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOver {
+    /// The program halted.
+    Halted,
+    /// The policy returned no input, the game can be continued with another [`Game::run`].
+    Stopped,
+}
+
+/// Drives an Intcode program with a [`Policy`], keeping track of the [`World`] it plays in.
+///
+/// A frame is drawn every time the program waits for input and once more when it halts, but only when
+/// the game is displayed with [`Game::display`].
+pub struct Game<P: Policy> {
+    computer: IntCodeComputer,
+    world: World<P::Tile>,
+    policy: P,
+    message: Vec<i64>,
+    display: Option<Duration>,
+}
+
+impl<P: Policy> Game<P> {
+    pub fn new(computer: IntCodeComputer, world: World<P::Tile>, policy: P) -> Self {
+        Self {
+            computer,
+            world,
+            policy,
+            message: Vec::with_capacity(P::MESSAGE_LEN),
+            display: None,
+        }
+    }
+
+    pub fn computer(&self) -> &IntCodeComputer {
+        &self.computer
+    }
+
+    /// The computer, e.g. to insert quarters into the arcade cabinet of day 13 before playing.
+    pub fn computer_mut(&mut self) -> &mut IntCodeComputer {
+        &mut self.computer
+    }
+
+    pub fn world(&self) -> &World<P::Tile> {
+        &self.world
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Draws every frame to the terminal, waiting `delay` after each one. `None` stops drawing.
+    pub fn display(&mut self, delay: Option<Duration>) {
+        self.display = delay;
+    }
+
+    /// The current frame: the map with the robot and the status line of the policy.
+    pub fn frame(&self) -> String {
+        let mut frame = self.world.render(|tile| self.policy.glyph(tile));
+        frame.push_str(&self.policy.status());
+        frame
+    }
+
+    /// Runs until the program halts or the policy stops the game.
+    pub fn run(&mut self) -> Result<GameOver, IntcodeError> {
+        loop {
+            match self.computer.resume()? {
+                RunState::Output(value) => {
+                    self.message.push(value);
+                    if self.message.len() == P::MESSAGE_LEN {
+                        self.policy.output(&mut self.world, &self.message);
+                        self.message.clear();
+                    }
+                }
+                RunState::NeedsInput => {
+                    self.draw();
+                    match self.policy.input(&mut self.world) {
+                        Some(value) => self.computer.push_input(value),
+                        None => return Ok(GameOver::Stopped),
+                    }
+                }
+                RunState::Halted => {
+                    self.draw();
+                    return Ok(GameOver::Halted);
+                }
+            }
+        }
+    }
+
+    fn draw(&self) {
+        if let Some(delay) = self.display {
+            // Clear the terminal and move the cursor to the top left corner
+            print!("\x1b[2J\x1b[H{}", self.frame());
+            let _ = stdout().flush();
+            thread::sleep(delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paints the panel below the robot white and turns right, four times.
+    struct Painter;
+
+    impl Policy for Painter {
+        type Tile = bool;
+        const MESSAGE_LEN: usize = 2;
+
+        fn output(&mut self, world: &mut World<bool>, message: &[i64]) {
+            let robot = world.robot.as_mut().unwrap();
+            world.map.set(robot.position, message[0] == 1);
+            robot.turn(if message[1] == 0 {
+                Turn::Left
+            } else {
+                Turn::Right
+            });
+            robot.advance();
+        }
+
+        fn input(&mut self, world: &mut World<bool>) -> Option<i64> {
+            let robot = world.robot.unwrap();
+            Some(
+                world
+                    .map
+                    .get(robot.position)
+                    .map_or(0, |&white| white as i64),
+            )
+        }
+
+        fn glyph(&self, &white: &bool) -> char {
+            if white {
+                '#'
+            } else {
+                '.'
+            }
+        }
+    }
+
+    /// Draws the screen and answers the joystick with a fixed position, the score is the last input.
+    struct Arcade {
+        joystick: Option<i64>,
+        score: i64,
+    }
+
+    impl Policy for Arcade {
+        type Tile = i64;
+        const MESSAGE_LEN: usize = 3;
+
+        fn output(&mut self, world: &mut World<i64>, message: &[i64]) {
+            match message {
+                [-1, 0, score] => self.score = *score,
+                [x, y, tile] => {
+                    world.map.set((*x, *y), *tile);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        fn input(&mut self, _: &mut World<i64>) -> Option<i64> {
+            self.joystick
+        }
+
+        fn glyph(&self, tile: &i64) -> char {
+            [' ', '#', 'x', '_', 'o'][*tile as usize]
+        }
+
+        fn status(&self) -> String {
+            format!("Score: {}\n", self.score)
+        }
+    }
+
+    /// Draws a wall at (0, 0) and a ball at (2, 0), then outputs the joystick input as score.
+    const ARCADE: &str = "104,0,104,0,104,1,104,2,104,0,104,4,3,100,104,-1,104,0,4,100,99";
+
+    #[test]
+    fn test_pose() {
+        let mut pose = Pose::new((0, 0), Direction::Up);
+        pose.turn(Turn::Left);
+        pose.advance();
+        assert_eq!(pose, Pose::new((-1, 0), Direction::Left));
+        assert_eq!(pose.ahead(), (-2, 0));
+        assert_eq!(Direction::Left.reverse(), Direction::Right);
+        assert_eq!(
+            Direction::Up
+                .turn(Turn::Left)
+                .turn(Turn::Left)
+                .turn(Turn::Left),
+            Direction::Right
+        );
+    }
+
+    #[test]
+    fn test_robot() {
+        // Reads the panel color, outputs white and turn right, four times
+        let computer = IntCodeComputer::new("3,15,104,1,104,1,1001,14,-1,14,1005,14,0,99,4,0");
+        let mut game = Game::new(
+            computer,
+            World::with_robot(Pose::new((0, 0), Direction::Up)),
+            Painter,
+        );
+        assert_eq!(game.run(), Ok(GameOver::Halted));
+
+        assert_eq!(game.world().map.len(), 4);
+        assert_eq!(game.world().map.bounds(), Some(((0, 0), (1, 1))));
+        assert_eq!(game.world().robot, Some(Pose::new((0, 0), Direction::Up)));
+        assert_eq!(game.frame(), "^#\n##\n");
+    }
+
+    #[test]
+    fn test_arcade() {
+        let arcade = Arcade {
+            joystick: Some(7),
+            score: 0,
+        };
+        let mut game = Game::new(IntCodeComputer::new(ARCADE), World::new(), arcade);
+        assert_eq!(game.run(), Ok(GameOver::Halted));
+        assert_eq!(game.frame(), "# o\nScore: 7\n");
+    }
+
+    #[test]
+    fn test_stop() {
+        let arcade = Arcade {
+            joystick: None,
+            score: 0,
+        };
+        let mut game = Game::new(IntCodeComputer::new(ARCADE), World::new(), arcade);
+        assert_eq!(game.run(), Ok(GameOver::Stopped));
+        assert_eq!(game.frame(), "# o\nScore: 0\n");
+
+        game.computer_mut().push_input(-1);
+        assert_eq!(game.run(), Ok(GameOver::Halted));
+        assert_eq!(game.policy().score, -1);
+    }
+}
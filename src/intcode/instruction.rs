@@ -0,0 +1,170 @@
+/// The operations understood by the Intcode computer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+/// Lookup table from the numeric opcode (the last two digits of an instruction) to its operation.
+const OPCODES: [Option<Opcode>; 100] = {
+    let mut table = [None; 100];
+    table[1] = Some(Opcode::Add);
+    table[2] = Some(Opcode::Multiply);
+    table[3] = Some(Opcode::Input);
+    table[4] = Some(Opcode::Output);
+    table[5] = Some(Opcode::JumpIfTrue);
+    table[6] = Some(Opcode::JumpIfFalse);
+    table[7] = Some(Opcode::LessThan);
+    table[8] = Some(Opcode::Equals);
+    table[9] = Some(Opcode::AdjustRelativeBase);
+    table[99] = Some(Opcode::Halt);
+    table
+};
+
+impl Opcode {
+    /// The numeric opcode, the last two digits of an instruction.
+    pub(crate) const fn code(self) -> i64 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Multiply => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustRelativeBase => 9,
+            Opcode::Halt => 99,
+        }
+    }
+
+    /// Looks up the operation for the last two digits of a raw instruction.
+    pub(crate) fn from_instruction(value: i64) -> Option<Self> {
+        if value < 0 {
+            return None;
+        }
+        OPCODES[(value % 100) as usize]
+    }
+
+    /// Looks up the operation for a mnemonic, ignoring case.
+    pub(crate) fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        OPCODES
+            .iter()
+            .flatten()
+            .copied()
+            .find(|opcode| opcode.mnemonic().eq_ignore_ascii_case(mnemonic))
+    }
+
+    /// The mnemonic used in disassembly listings.
+    pub(crate) const fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "ADD",
+            Opcode::Multiply => "MUL",
+            Opcode::Input => "IN",
+            Opcode::Output => "OUT",
+            Opcode::JumpIfTrue => "JNZ",
+            Opcode::JumpIfFalse => "JZ",
+            Opcode::LessThan => "LT",
+            Opcode::Equals => "EQ",
+            Opcode::AdjustRelativeBase => "ARB",
+            Opcode::Halt => "HLT",
+        }
+    }
+
+    /// Whether the last parameter is the address the result is written to.
+    pub(crate) const fn writes(self) -> bool {
+        matches!(
+            self,
+            Opcode::Add | Opcode::Multiply | Opcode::Input | Opcode::LessThan | Opcode::Equals
+        )
+    }
+
+    /// The amount of parameters following the instruction in memory.
+    pub(crate) const fn parameter_count(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+}
+
+/// How a parameter is interpreted, mode 0 is an address pointer, mode 1 is a direct value
+/// and mode 2 is an address pointer offset by the relative base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    pub(crate) const fn digit(self) -> i64 {
+        match self {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        }
+    }
+
+    const fn from_digit(digit: i64) -> Option<Self> {
+        match digit {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded instruction, the operation together with the modes of its parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Instruction {
+    pub(crate) opcode: Opcode,
+    pub(crate) modes: [Mode; 3],
+}
+
+impl Instruction {
+    /// Decodes the raw value at the instruction pointer, `None` if the opcode or a mode is unknown.
+    pub(crate) fn decode(value: i64) -> Option<Self> {
+        let opcode = Opcode::from_instruction(value)?;
+
+        let mut modes = [Mode::Position; 3];
+        let mut digits = value / 100;
+        for mode in modes.iter_mut().take(opcode.parameter_count()) {
+            *mode = Mode::from_digit(digits % 10)?;
+            digits /= 10;
+        }
+        // Any digits left over do not belong to a parameter
+        if digits != 0 {
+            return None;
+        }
+
+        Some(Self { opcode, modes })
+    }
+
+    /// Encodes the instruction back into its raw value.
+    pub(crate) fn encode(&self) -> i64 {
+        self.modes
+            .iter()
+            .take(self.opcode.parameter_count())
+            .rev()
+            .fold(0, |digits, mode| digits * 10 + mode.digit())
+            * 100
+            + self.opcode.code()
+    }
+
+    /// The amount of memory cells taken up by the instruction and its parameters.
+    pub(crate) const fn length(&self) -> usize {
+        self.opcode.parameter_count() + 1
+    }
+}
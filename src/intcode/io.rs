@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+
+/// A source of values for the input instruction, see [`IntCodeComputer::run_with`].
+///
+/// [`IntCodeComputer::run_with`]: crate::intcode::IntCodeComputer::run_with
+pub trait IntcodeInput {
+    /// The next input value, `None` when the source is exhausted.
+    fn read(&mut self) -> Option<i64>;
+}
+
+/// A sink for the values of the output instruction, see [`IntCodeComputer::run_with`].
+///
+/// [`IntCodeComputer::run_with`]: crate::intcode::IntCodeComputer::run_with
+pub trait IntcodeOutput {
+    fn write(&mut self, value: i64);
+}
+
+impl<T: IntcodeInput + ?Sized> IntcodeInput for &mut T {
+    fn read(&mut self) -> Option<i64> {
+        (**self).read()
+    }
+}
+
+impl<T: IntcodeOutput + ?Sized> IntcodeOutput for &mut T {
+    fn write(&mut self, value: i64) {
+        (**self).write(value)
+    }
+}
+
+/// Reads input from any iterator, e.g. `IterInput([1, 2].into_iter())`.
+pub struct IterInput<I>(pub I);
+
+impl<I: Iterator<Item = i64>> IntcodeInput for IterInput<I> {
+    fn read(&mut self) -> Option<i64> {
+        self.0.next()
+    }
+}
+
+/// Reads input by calling a closure, e.g. to compute a robot command from its surroundings.
+pub struct FnInput<F>(pub F);
+
+impl<F: FnMut() -> Option<i64>> IntcodeInput for FnInput<F> {
+    fn read(&mut self) -> Option<i64> {
+        (self.0)()
+    }
+}
+
+/// Writes output by calling a closure.
+pub struct FnOutput<F>(pub F);
+
+impl<F: FnMut(i64)> IntcodeOutput for FnOutput<F> {
+    fn write(&mut self, value: i64) {
+        (self.0)(value)
+    }
+}
+
+impl IntcodeInput for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl IntcodeOutput for VecDeque<i64> {
+    fn write(&mut self, value: i64) {
+        self.push_back(value)
+    }
+}
+
+/// Collects every output value silently.
+impl IntcodeOutput for Vec<i64> {
+    fn write(&mut self, value: i64) {
+        self.push(value)
+    }
+}
+
+/// Blocks until a value is received, the input is exhausted once every sender is dropped.
+impl IntcodeInput for Receiver<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+/// Sends every output value, values are dropped once the receiver is gone.
+impl IntcodeOutput for Sender<i64> {
+    fn write(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
+
+/// Discards every output value.
+pub struct Discard;
+
+impl IntcodeOutput for Discard {
+    fn write(&mut self, _value: i64) {}
+}
+
+/// Prints every output value on its own line, for command line tools.
+pub struct Print;
+
+impl IntcodeOutput for Print {
+    fn write(&mut self, value: i64) {
+        println!("{value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+    use crate::intcode::IntCodeComputer;
+
+    /// Outputs the sum of every pair of inputs.
+    const PROGRAM: &str = "3,13,3,14,1,13,14,15,4,15,1105,1,0,0,0,0";
+
+    #[test]
+    fn test_iterator_and_collector() {
+        let mut output = vec![];
+        let mut computer = IntCodeComputer::new(PROGRAM);
+        let result = computer.run_with(IterInput([1, 2, 3, 4].into_iter()), &mut output);
+        assert!(result.is_err());
+        assert_eq!(output, vec![3, 7]);
+    }
+
+    #[test]
+    fn test_closures() {
+        let mut next = 0;
+        let mut sum = 0;
+        let mut computer = IntCodeComputer::new(PROGRAM);
+        let _ = computer.run_with(
+            FnInput(|| {
+                next += 1;
+                (next <= 6).then_some(next)
+            }),
+            FnOutput(|value| sum += value),
+        );
+        assert_eq!(sum, 21);
+    }
+
+    #[test]
+    fn test_channels() {
+        let (input, receiver) = channel();
+        let (sender, output) = channel();
+        [5, 6]
+            .into_iter()
+            .for_each(|value| input.send(value).unwrap());
+        drop(input);
+
+        let mut computer = IntCodeComputer::new(PROGRAM);
+        let _ = computer.run_with(receiver, sender);
+        assert_eq!(output.iter().collect::<Vec<_>>(), vec![11]);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::intcode::memory::Memory;
+
+/// Detects a computer that provably runs forever without doing any input or output.
+///
+/// Memory is summarized by a fingerprint that is updated on every write, the XOR of a hash of every
+/// non-zero cell. The state (instruction pointer, relative base, fingerprint) after every jump is
+/// compared with a single checkpoint that moves forward after a doubling amount of jumps (Brent's cycle
+/// detection), so a long loop uses no more memory than a short one. A repeated state is only a
+/// candidate, the loop is proven once that state is reached again with exactly the same memory.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LoopDetector {
+    fingerprint: u64,
+    checkpoint: Option<(usize, i64, u64)>,
+    /// The jumps since the checkpoint was taken and the amount after which the next one is taken.
+    jumps: u64,
+    power: u64,
+    candidate: Option<((usize, i64, u64), Memory)>,
+}
+
+impl LoopDetector {
+    pub(crate) fn new(memory: &Memory) -> Self {
+        let fingerprint = memory
+            .cells()
+            .into_iter()
+            .fold(0, |fingerprint, (address, value)| {
+                fingerprint ^ cell_hash(address, value)
+            });
+        Self {
+            fingerprint,
+            ..Self::default()
+        }
+    }
+
+    /// Updates the fingerprint for a write of `new` over `old` at `address`.
+    pub(crate) fn write(&mut self, address: usize, old: i64, new: i64) {
+        self.fingerprint ^= cell_hash(address, old) ^ cell_hash(address, new);
+    }
+
+    /// Input or output happened, the states recorded so far no longer prove anything.
+    pub(crate) fn reset(&mut self) {
+        self.checkpoint = None;
+        self.jumps = 0;
+        self.power = 0;
+        self.candidate = None;
+    }
+
+    /// Records the state after a jump, returns `true` once the computer is proven to loop forever.
+    pub(crate) fn jump(
+        &mut self,
+        instruction_pointer: usize,
+        relative_base: i64,
+        memory: &Memory,
+    ) -> bool {
+        let state = (instruction_pointer, relative_base, self.fingerprint);
+        if let Some((candidate, snapshot)) = &self.candidate {
+            if *candidate == state {
+                // Memory grown with zeroes behaves the same, which equality already ignores
+                if snapshot == memory {
+                    return true;
+                }
+                // A fingerprint collision, keep looking
+                self.candidate = None;
+            }
+        }
+
+        if self.checkpoint == Some(state) && self.candidate.is_none() {
+            self.candidate = Some((state, memory.clone()));
+        }
+        self.jumps += 1;
+        if self.jumps >= self.power {
+            self.checkpoint = Some(state);
+            self.jumps = 0;
+            self.power = (self.power * 2).max(1);
+        }
+        false
+    }
+}
+
+/// A well mixed hash of a single memory cell (splitmix64), 0 for empty cells.
+fn cell_hash(address: usize, value: i64) -> u64 {
+    if value == 0 {
+        return 0;
+    }
+    let mut hash = (address as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (value as u64);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_cycle() {
+        // A counter that runs up to 5000 without repeating, then wraps around every 1000 jumps
+        let counter = |jump: i64| {
+            if jump < 5000 {
+                jump
+            } else {
+                5000 + (jump - 5000) % 1000
+            }
+        };
+        let mut memory = Memory::from(vec![0]);
+        let mut detector = LoopDetector::new(&memory);
+        let proven = (1..100_000).find(|&jump| {
+            let (old, new) = (memory.get(0), counter(jump));
+            memory.set(0, new);
+            detector.write(0, old, new);
+            detector.jump(0, 0, &memory)
+        });
+        assert!(proven.is_some_and(|jump| jump > 6000 && jump < 20_000));
+
+        // Counting without ever repeating is not a loop
+        let mut memory = Memory::from(vec![0]);
+        let mut detector = LoopDetector::new(&memory);
+        let proven = (1..100_000).any(|jump| {
+            detector.write(0, jump - 1, jump);
+            memory.set(0, jump);
+            detector.jump(0, 0, &memory)
+        });
+        assert!(!proven);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::intcode::snapshot::{Page, PAGE_SIZE};
+
+/// Pages below this index are found by indexing, pages beyond it through a hash map.
+const NEAR_PAGES: usize = 4096;
+
+/// With [`MemoryKind::Auto`], dense memory turns sparse once a write lands this many cells past its end.
+pub const SPARSE_DISTANCE: usize = 1 << 20;
+
+/// How an [`IntCodeComputer`] stores its memory, see [`IntCodeComputer::set_memory_kind`].
+///
+/// [`IntCodeComputer`]: crate::intcode::IntCodeComputer
+/// [`IntCodeComputer::set_memory_kind`]: crate::intcode::IntCodeComputer::set_memory_kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MemoryKind {
+    /// Dense until a write lands more than [`SPARSE_DISTANCE`] cells past the end, sparse from then on.
+    #[default]
+    Auto,
+    /// A single vector grown to the highest address written, the fastest for programs that stay close to their end.
+    Dense,
+    /// Pages of cells allocated on their first write, for programs that write far beyond their end.
+    Sparse,
+}
+
+/// The memory of an Intcode computer. Cells that were never written read as 0 and both backends behave
+/// identically, they only differ in speed and size.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    backend: Backend,
+    kind: MemoryKind,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Backend {
+    Dense(Vec<i64>),
+    Sparse(SparseMemory),
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Dense(vec![])
+    }
+}
+
+impl Memory {
+    pub fn new(cells: Vec<i64>, kind: MemoryKind) -> Self {
+        let backend = match kind {
+            MemoryKind::Auto | MemoryKind::Dense => Backend::Dense(cells),
+            MemoryKind::Sparse => Backend::Sparse(SparseMemory::new(&cells)),
+        };
+        Self { backend, kind }
+    }
+
+    /// Memory of `length` cells holding `cells`, stored sparsely if `sparse` (e.g. an [`MemoryKind::Auto`]
+    /// memory that already turned sparse) or if `length` is beyond [`SPARSE_DISTANCE`], so a length that was
+    /// never allocated is not allocated now. Fails if a cell lies beyond `length`.
+    pub(crate) fn from_cells(
+        cells: &[(usize, i64)],
+        length: usize,
+        kind: MemoryKind,
+        sparse: bool,
+    ) -> Result<Self, usize> {
+        if let Some(&(address, _)) = cells.iter().find(|&&(address, _)| address >= length) {
+            return Err(address);
+        }
+        let backend = if sparse || kind == MemoryKind::Sparse || length > SPARSE_DISTANCE {
+            let mut memory = SparseMemory::default();
+            cells
+                .iter()
+                .for_each(|&(address, value)| memory.set(address, value));
+            memory.length = length;
+            Backend::Sparse(memory)
+        } else {
+            let mut memory = vec![0; length];
+            cells
+                .iter()
+                .for_each(|&(address, value)| memory[address] = value);
+            Backend::Dense(memory)
+        };
+        Ok(Self { backend, kind })
+    }
+
+    /// The value stored at `address`, memory beyond the written cells reads as 0.
+    #[inline]
+    pub fn get(&self, address: usize) -> i64 {
+        match &self.backend {
+            Backend::Dense(cells) => cells.get(address).copied().unwrap_or(0),
+            Backend::Sparse(sparse) => sparse.get(address),
+        }
+    }
+
+    /// Overwrites the value stored at `address`, growing the memory to fit it.
+    #[inline]
+    pub(crate) fn set(&mut self, address: usize, value: i64) {
+        match &mut self.backend {
+            Backend::Dense(cells) if address < cells.len() => cells[address] = value,
+            Backend::Dense(cells)
+                if self.kind == MemoryKind::Auto && address - cells.len() > SPARSE_DISTANCE =>
+            {
+                let mut sparse = SparseMemory::new(cells);
+                sparse.set(address, value);
+                self.backend = Backend::Sparse(sparse);
+            }
+            Backend::Dense(cells) => {
+                cells.resize(address + 1, 0);
+                cells[address] = value;
+            }
+            Backend::Sparse(sparse) => sparse.set(address, value),
+        }
+    }
+
+    /// One past the highest address that was written or loaded.
+    pub fn len(&self) -> usize {
+        match &self.backend {
+            Backend::Dense(cells) => cells.len(),
+            Backend::Sparse(sparse) => sparse.length,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the memory is currently stored sparsely.
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.backend, Backend::Sparse(_))
+    }
+
+    pub fn kind(&self) -> MemoryKind {
+        self.kind
+    }
+
+    /// Switches the backend, converting sparse memory to dense allocates every cell up to [`Memory::len`].
+    pub(crate) fn set_kind(&mut self, kind: MemoryKind) {
+        self.backend = match (std::mem::take(&mut self.backend), kind) {
+            (Backend::Dense(cells), MemoryKind::Sparse) => {
+                Backend::Sparse(SparseMemory::new(&cells))
+            }
+            (Backend::Sparse(sparse), MemoryKind::Dense) => Backend::Dense(sparse.to_vec()),
+            (backend, _) => backend,
+        };
+        self.kind = kind;
+    }
+
+    /// Every cell from address 0 up to [`Memory::len`].
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        (0..self.len()).map(|address| self.get(address))
+    }
+
+    pub fn to_vec(&self) -> Vec<i64> {
+        match &self.backend {
+            Backend::Dense(cells) => cells.clone(),
+            Backend::Sparse(sparse) => sparse.to_vec(),
+        }
+    }
+
+    /// The first address from `address` on that is backed by memory, skipping the pages of sparse memory
+    /// that were never written. `None` past the end of memory.
+    pub fn next_mapped(&self, address: usize) -> Option<usize> {
+        match &self.backend {
+            Backend::Dense(cells) => (address < cells.len()).then_some(address),
+            Backend::Sparse(sparse) => sparse.next_mapped(address),
+        }
+    }
+
+    /// The addresses and values of every cell that is not 0, by address. Fast for sparse memory, however
+    /// far it reaches.
+    pub fn cells(&self) -> Vec<(usize, i64)> {
+        match &self.backend {
+            Backend::Dense(cells) => cells
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, value)| value != 0)
+                .collect(),
+            Backend::Sparse(sparse) => sparse.cells(),
+        }
+    }
+
+    pub(crate) fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    pub(crate) fn backend_mut(&mut self) -> &mut Backend {
+        &mut self.backend
+    }
+}
+
+/// Memories are equal when every address holds the same value, regardless of the backend or how far
+/// each one was grown with zeroes.
+impl PartialEq for Memory {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells() == other.cells()
+    }
+}
+
+impl Eq for Memory {}
+
+impl From<Vec<i64>> for Memory {
+    fn from(cells: Vec<i64>) -> Self {
+        Memory::new(cells, MemoryKind::Auto)
+    }
+}
+
+/// Memory split into copy-on-write pages that are only allocated once written. Pages are shared with
+/// clones and snapshots until either side writes to them.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SparseMemory {
+    /// Pages below [`NEAR_PAGES`] by index, these hold the program and are accessed all the time.
+    near: Vec<Option<Page>>,
+    far: HashMap<usize, Page>,
+    length: usize,
+}
+
+impl SparseMemory {
+    /// Every page of `cells` is allocated, even when it only holds 0, so the program stays mapped.
+    fn new(cells: &[i64]) -> Self {
+        let mut sparse = Self::default();
+        for (index, chunk) in cells.chunks(PAGE_SIZE).enumerate() {
+            let mut page = [0; PAGE_SIZE];
+            page[..chunk.len()].copy_from_slice(chunk);
+            sparse.insert(index, Arc::new(page));
+        }
+        sparse.length = cells.len();
+        sparse
+    }
+
+    fn page(&self, index: usize) -> Option<&Page> {
+        if index < NEAR_PAGES {
+            self.near.get(index)?.as_ref()
+        } else {
+            self.far.get(&index)
+        }
+    }
+
+    fn insert(&mut self, index: usize, page: Page) {
+        if index < NEAR_PAGES {
+            if index >= self.near.len() {
+                self.near.resize(index + 1, None);
+            }
+            self.near[index] = Some(page);
+        } else {
+            self.far.insert(index, page);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, address: usize) -> i64 {
+        self.page(address / PAGE_SIZE)
+            .map_or(0, |page| page[address % PAGE_SIZE])
+    }
+
+    fn set(&mut self, address: usize, value: i64) {
+        let index = address / PAGE_SIZE;
+        let page = if index < NEAR_PAGES {
+            if index >= self.near.len() {
+                self.near.resize(index + 1, None);
+            }
+            self.near[index].get_or_insert_with(|| Arc::new([0; PAGE_SIZE]))
+        } else {
+            self.far
+                .entry(index)
+                .or_insert_with(|| Arc::new([0; PAGE_SIZE]))
+        };
+        Arc::make_mut(page)[address % PAGE_SIZE] = value;
+        self.length = self.length.max(address + 1);
+    }
+
+    fn next_mapped(&self, address: usize) -> Option<usize> {
+        let index = self
+            .pages()
+            .into_iter()
+            .map(|(index, _)| index)
+            .find(|&index| index >= address / PAGE_SIZE)?;
+        Some(address.max(index * PAGE_SIZE)).filter(|&address| address < self.length)
+    }
+
+    /// Every allocated page with its index, by index.
+    pub(crate) fn pages(&self) -> Vec<(usize, &Page)> {
+        let near = self
+            .near
+            .iter()
+            .enumerate()
+            .filter_map(|(index, page)| Some((index, page.as_ref()?)));
+        let mut pages = near
+            .chain(self.far.iter().map(|(&index, page)| (index, page)))
+            .collect::<Vec<_>>();
+        pages.sort_unstable_by_key(|&(index, _)| index);
+        pages
+    }
+
+    fn cells(&self) -> Vec<(usize, i64)> {
+        self.pages()
+            .into_iter()
+            .flat_map(|(index, page)| {
+                page.iter()
+                    .enumerate()
+                    .map(move |(offset, &value)| (index * PAGE_SIZE + offset, value))
+            })
+            .filter(|&(_, value)| value != 0)
+            .collect()
+    }
+
+    fn to_vec(&self) -> Vec<i64> {
+        let mut cells = vec![0; self.length];
+        for (address, value) in self.cells() {
+            cells[address] = value;
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        let program = vec![1, 2, 3, 0, 5];
+        let mut memories = [MemoryKind::Auto, MemoryKind::Dense, MemoryKind::Sparse]
+            .map(|kind| Memory::new(program.clone(), kind));
+        for memory in &mut memories {
+            memory.set(3, 4);
+            memory.set(1000, 7);
+            memory.set(PAGE_SIZE * NEAR_PAGES + 3, 9);
+        }
+
+        for memory in &memories {
+            assert_eq!(memory, &memories[0]);
+            assert_eq!(memory.len(), PAGE_SIZE * NEAR_PAGES + 4);
+            assert_eq!(memory.get(3), 4);
+            assert_eq!(memory.get(999), 0);
+            assert_eq!(memory.get(PAGE_SIZE * NEAR_PAGES + 3), 9);
+            assert_eq!(memory.get(usize::MAX), 0);
+            assert_eq!(
+                memory.cells(),
+                [
+                    (0, 1),
+                    (1, 2),
+                    (2, 3),
+                    (3, 4),
+                    (4, 5),
+                    (1000, 7),
+                    (PAGE_SIZE * NEAR_PAGES + 3, 9)
+                ]
+            );
+            assert_eq!(memory.next_mapped(5), Some(5));
+            assert_eq!(memory.next_mapped(PAGE_SIZE * NEAR_PAGES + 4), None);
+        }
+        assert_eq!(memories[2].to_vec(), memories[1].to_vec());
+
+        // Sparse memory skips the pages that were never written
+        assert_eq!(
+            memories[2].next_mapped(PAGE_SIZE),
+            Some(1000 / PAGE_SIZE * PAGE_SIZE)
+        );
+        assert_eq!(memories[2].next_mapped(1024), Some(PAGE_SIZE * NEAR_PAGES));
+        assert_eq!(memories[1].next_mapped(1001), Some(1001));
+    }
+
+    #[test]
+    fn test_auto() {
+        let mut memory = Memory::from(vec![1, 2, 3]);
+        memory.set(SPARSE_DISTANCE, 1);
+        assert!(!memory.is_sparse());
+
+        memory.set(1 << 40, 2);
+        assert!(memory.is_sparse());
+        assert_eq!(memory.len(), (1 << 40) + 1);
+        assert_eq!(memory.get(SPARSE_DISTANCE), 1);
+        assert_eq!(memory.get(1 << 40), 2);
+
+        let mut dense = Memory::new(vec![1, 2, 3], MemoryKind::Dense);
+        dense.set(SPARSE_DISTANCE * 2, 1);
+        assert!(!dense.is_sparse());
+    }
+
+    #[test]
+    fn test_shared_pages() {
+        let mut memory = Memory::new(vec![1; PAGE_SIZE * 2], MemoryKind::Sparse);
+        let copy = memory.clone();
+        memory.set(0, 2);
+
+        let (Backend::Sparse(a), Backend::Sparse(b)) = (memory.backend(), copy.backend()) else {
+            panic!("not sparse");
+        };
+        let shared = a
+            .pages()
+            .into_iter()
+            .zip(b.pages())
+            .filter(|((_, a), (_, b))| Arc::ptr_eq(a, b))
+            .count();
+        assert_eq!(shared, 1);
+        assert_eq!(copy.get(0), 1);
+    }
+}
@@ -0,0 +1,35 @@
+//! Shared Intcode computer used by every Intcode puzzle of 2019.
+//!
+//! Load a program with [`IntCodeComputer::new`], patch memory with [`IntCodeComputer::set`],
+//! queue input with [`IntCodeComputer::push_input`] and execute it with [`IntCodeComputer::run`].
+//! Interactive programs are driven with [`IntCodeComputer::resume`], which pauses on every [`RunState`].
+//! Programs that misbehave fail with an [`IntcodeError`] instead of panicking.
+//! The computer never prints, use [`IntCodeComputer::run_with`] to connect it to any [`io`] source and sink.
+pub mod adventure;
+pub mod ascii;
+pub mod assembler;
+pub mod compiled;
+mod computer;
+#[cfg(test)]
+mod conformance;
+pub mod debugger;
+pub mod disassembler;
+mod error;
+pub mod game;
+mod instruction;
+pub mod io;
+mod loops;
+pub mod memory;
+pub mod network;
+pub mod profile;
+pub mod session;
+mod snapshot;
+pub mod springscript;
+pub mod sweep;
+pub mod symbolic;
+pub mod topology;
+
+pub use computer::{IntCodeComputer, RunState};
+pub use error::IntcodeError;
+pub use memory::{Memory, MemoryKind};
+pub use snapshot::Snapshot;
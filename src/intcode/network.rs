@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::intcode::{IntCodeComputer, IntcodeError, RunState};
+
+/// A packet sent over the network, the `destination` is the address of the receiving computer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Events the network hands to the NAT hook of [`Network::run`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkEvent {
+    /// A packet addressed to a computer outside the network, e.g. the NAT at address 255.
+    Packet(Packet),
+    /// Every queue is empty and no computer sent a packet during the last round.
+    Idle,
+}
+
+/// How the network continues after the NAT hook handled an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NatAction<T> {
+    Continue,
+    Send(Packet),
+    Stop(T),
+}
+
+/// Why a network stopped before the NAT hook stopped it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetworkError {
+    Intcode(IntcodeError),
+    /// A packet was sent to a computer that already halted, so it could never be received.
+    HaltedDestination(Packet),
+    /// Every computer halted, so no packet will ever be sent again.
+    AllHalted,
+}
+
+impl Error for NetworkError {}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Intcode(error) => write!(f, "{error}"),
+            NetworkError::HaltedDestination(packet) => {
+                write!(
+                    f,
+                    "Network: packet ({}, {}) sent to halted computer {}",
+                    packet.x, packet.y, packet.destination
+                )
+            }
+            NetworkError::AllHalted => write!(f, "Network: every computer halted"),
+        }
+    }
+}
+
+impl From<IntcodeError> for NetworkError {
+    fn from(error: IntcodeError) -> Self {
+        NetworkError::Intcode(error)
+    }
+}
+
+/// A network of Intcode computers that address packets to each other (day 23).
+///
+/// The network is scheduled round robin on a single thread, every round each computer receives its
+/// queued packets (or -1 when its queue is empty) and runs until it waits for input again. Halted
+/// computers are skipped.
+pub struct Network {
+    computers: Vec<IntCodeComputer>,
+    queues: Vec<VecDeque<Packet>>,
+    pending_output: Vec<Vec<i64>>,
+    halted: Vec<bool>,
+}
+
+impl Network {
+    /// Boots `size` copies of `program`, each receiving its own address as first input.
+    pub fn new(program: &IntCodeComputer, size: usize) -> Self {
+        let computers = (0..size)
+            .map(|address| {
+                let mut computer = program.clone();
+                computer.push_input(address as i64);
+                computer
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            computers,
+            queues: vec![VecDeque::new(); size],
+            pending_output: vec![vec![]; size],
+            halted: vec![false; size],
+        }
+    }
+
+    /// The amount of computers in the network.
+    pub fn len(&self) -> usize {
+        self.computers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.computers.is_empty()
+    }
+
+    /// The computer listening on `address`.
+    pub fn computer(&self, address: usize) -> &IntCodeComputer {
+        &self.computers[address]
+    }
+
+    /// Queues a packet for its destination, returns it back if the destination is outside the network.
+    /// Fails if the destination already halted.
+    pub fn send(&mut self, packet: Packet) -> Result<Option<Packet>, NetworkError> {
+        let Ok(destination) = usize::try_from(packet.destination) else {
+            return Ok(Some(packet));
+        };
+        match self.queues.get_mut(destination) {
+            Some(_) if self.halted[destination] => Err(NetworkError::HaltedDestination(packet)),
+            Some(queue) => {
+                queue.push_back(packet);
+                Ok(None)
+            }
+            None => Ok(Some(packet)),
+        }
+    }
+
+    /// Runs a single round over every computer, returns the packets addressed outside the network
+    /// and whether any packet was sent or received at all. Fails once every computer halted.
+    pub fn round(&mut self) -> Result<(Vec<Packet>, bool), NetworkError> {
+        let mut external = vec![];
+        let mut active = false;
+
+        for address in 0..self.computers.len() {
+            if self.halted[address] {
+                continue;
+            }
+            let computer = &mut self.computers[address];
+            if self.queues[address].is_empty() {
+                computer.push_input(-1);
+            } else {
+                active = true;
+                for packet in self.queues[address].drain(..) {
+                    computer.push_input(packet.x);
+                    computer.push_input(packet.y);
+                }
+            }
+
+            let mut sent = vec![];
+            // Run until the computer waits for input again or halts
+            loop {
+                match computer.resume()? {
+                    RunState::Output(value) => {
+                        let pending = &mut self.pending_output[address];
+                        pending.push(value);
+                        if pending.len() == 3 {
+                            sent.push(Packet {
+                                destination: pending[0],
+                                x: pending[1],
+                                y: pending[2],
+                            });
+                            pending.clear();
+                        }
+                    }
+                    RunState::NeedsInput => break,
+                    RunState::Halted => {
+                        self.halted[address] = true;
+                        break;
+                    }
+                }
+            }
+
+            // Every packet is queued before a packet to a halted computer is reported
+            let mut error = None;
+            for packet in sent {
+                active = true;
+                match self.send(packet) {
+                    Ok(packet) => external.extend(packet),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                }
+            }
+            if let Some(error) = error {
+                return Err(error);
+            }
+        }
+
+        if self.halted.iter().all(|&halted| halted) {
+            return Err(NetworkError::AllHalted);
+        }
+        Ok((external, active))
+    }
+
+    /// Whether every queue is empty, so the next round only hands out -1.
+    pub fn queues_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Runs rounds until the `nat` hook stops the network, the hook receives every packet addressed
+    /// outside the network and is notified whenever the network is idle.
+    pub fn run<T>(
+        &mut self,
+        mut nat: impl FnMut(NetworkEvent) -> NatAction<T>,
+    ) -> Result<T, NetworkError> {
+        loop {
+            let (external, active) = self.round()?;
+
+            let mut events = external
+                .into_iter()
+                .map(NetworkEvent::Packet)
+                .collect::<Vec<_>>();
+            if !active && self.queues_empty() {
+                events.push(NetworkEvent::Idle);
+            }
+
+            for event in events {
+                match nat(event) {
+                    NatAction::Continue => {}
+                    NatAction::Send(packet) => {
+                        // Packets the NAT sends outside the network are dropped
+                        self.send(packet)?;
+                    }
+                    NatAction::Stop(result) => return Ok(result),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Address 0 sends (5, 6) to address 1, address 1 forwards every packet it receives to 255.
+    const PROGRAM: &str = "3,100,1005,100,12,104,1,104,5,104,6,99,3,101,1008,101,-1,103,1005,103,12,3,102,104,255,4,101,4,102,1105,1,12";
+
+    #[test]
+    fn test_routing() {
+        let mut network = Network::new(&IntCodeComputer::new(PROGRAM), 2);
+        let result = network.run(|event| match event {
+            NetworkEvent::Packet(packet) => NatAction::Stop(packet),
+            NetworkEvent::Idle => panic!("network went idle"),
+        });
+        assert_eq!(
+            result,
+            Ok(Packet {
+                destination: 255,
+                x: 5,
+                y: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_nat() {
+        let mut network = Network::new(&IntCodeComputer::new(PROGRAM), 2);
+        let mut received = vec![];
+        let result = network.run(|event| match event {
+            NetworkEvent::Packet(packet) => {
+                received.push(packet.y);
+                NatAction::Continue
+            }
+            NetworkEvent::Idle if received.len() < 2 => NatAction::Send(Packet {
+                destination: 1,
+                x: 0,
+                y: received[0] * 2,
+            }),
+            NetworkEvent::Idle => NatAction::Stop(received.clone()),
+        });
+        assert_eq!(result, Ok(vec![6, 12]));
+    }
+
+    #[test]
+    fn test_halted() {
+        // Address 0 halts after sending its packet, so a packet to it can never be received
+        let mut network = Network::new(&IntCodeComputer::new(PROGRAM), 2);
+        let result = network.run(|event| match event {
+            NetworkEvent::Packet(_) => NatAction::<()>::Continue,
+            NetworkEvent::Idle => NatAction::Send(Packet {
+                destination: 0,
+                x: 1,
+                y: 2,
+            }),
+        });
+        assert_eq!(
+            result,
+            Err(NetworkError::HaltedDestination(Packet {
+                destination: 0,
+                x: 1,
+                y: 2
+            }))
+        );
+        // Only the -1 of the first round is left, halted computers are no longer fed
+        assert_eq!(network.computer(0).pending_input().count(), 1);
+
+        // Address 0 halts right away, the packet address 1 sends itself after one to 0 is still queued
+        let program = "3,100,1006,100,22,104,0,104,1,104,2,104,1,104,3,104,4,3,101,1105,1,17,99";
+        let mut network = Network::new(&IntCodeComputer::new(program), 2);
+        assert_eq!(
+            network.round(),
+            Err(NetworkError::HaltedDestination(Packet {
+                destination: 0,
+                x: 1,
+                y: 2
+            }))
+        );
+        assert_eq!(
+            network.queues[1],
+            [Packet {
+                destination: 1,
+                x: 3,
+                y: 4
+            }]
+        );
+
+        // Both computers halt at once
+        let mut network = Network::new(&IntCodeComputer::new("3,100,99"), 2);
+        assert_eq!(
+            network.run(|_| NatAction::<()>::Continue),
+            Err(NetworkError::AllHalted)
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tinyjson::JsonValue;
+
+use crate::intcode::disassembler::{disassemble, disassemble_at, Line};
+use crate::intcode::instruction::Opcode;
+use crate::intcode::memory::Memory;
+
+/// Set by `cargo solve --profile`, every computer is profiled and a table is printed after every part.
+pub const PROFILE_VAR: &str = "INTCODE_PROFILE";
+/// Set by `cargo solve --profile-json <dir>`, every computer is profiled and a JSON report is written
+/// to `<dir>/<day>-<part>.json` after every part.
+pub const PROFILE_JSON_VAR: &str = "INTCODE_PROFILE_JSON";
+
+/// The amount of hot addresses and loops shown in the table.
+const TOP: usize = 10;
+
+/// Profiles of dropped computers, collected while profiling is enabled through the environment.
+static COLLECTED: Mutex<Option<Profile>> = Mutex::new(None);
+/// Set while the benchmark samples run, see [`pause`].
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Where the computer spent its time and what it touched, see [`IntCodeComputer::set_profiling`].
+///
+/// [`IntCodeComputer::set_profiling`]: crate::intcode::IntCodeComputer::set_profiling
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    /// The memory when profiling started, to list the instructions that never ran. Shared by the
+    /// profiles of clones and merged profiles, only the counters are their own.
+    program: Arc<Memory>,
+    instructions: u64,
+    opcodes: BTreeMap<&'static str, u64>,
+    /// Executions per instruction address.
+    executed: BTreeMap<usize, u64>,
+    reads: BTreeMap<usize, u64>,
+    writes: BTreeMap<usize, u64>,
+    /// Taken backward jumps by (from, to).
+    loops: HashMap<(usize, usize), u64>,
+}
+
+/// A backward jump that was taken, the instructions from `start` to `end` form a loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HotLoop {
+    pub start: usize,
+    pub end: usize,
+    pub iterations: u64,
+}
+
+impl Profile {
+    pub(crate) fn new(program: &Memory) -> Self {
+        Self {
+            program: Arc::new(program.clone()),
+            ..Self::default()
+        }
+    }
+
+    /// An empty profile of the same program, for a clone of the profiled computer.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            program: Arc::clone(&self.program),
+            ..Self::default()
+        }
+    }
+
+    /// Records an executed instruction with the addresses it read and wrote and the target of a taken jump.
+    pub(crate) fn record(
+        &mut self,
+        address: usize,
+        opcode: Opcode,
+        reads: &[usize],
+        write: Option<usize>,
+        jump: Option<usize>,
+    ) {
+        self.instructions += 1;
+        *self.opcodes.entry(opcode.mnemonic()).or_default() += 1;
+        increment(&mut self.executed, address);
+        reads
+            .iter()
+            .for_each(|&read| increment(&mut self.reads, read));
+        if let Some(write) = write {
+            increment(&mut self.writes, write);
+        }
+        if let Some(target) = jump.filter(|&target| target <= address) {
+            *self.loops.entry((address, target)).or_default() += 1;
+        }
+    }
+
+    /// Adds the counts of `other`, e.g. of every computer used to solve a puzzle.
+    pub fn merge(&mut self, other: &Profile) {
+        if self.program.is_empty() {
+            self.program = Arc::clone(&other.program);
+        }
+        self.instructions += other.instructions;
+        for (mnemonic, count) in &other.opcodes {
+            *self.opcodes.entry(mnemonic).or_default() += count;
+        }
+        add(&mut self.executed, &other.executed);
+        add(&mut self.reads, &other.reads);
+        add(&mut self.writes, &other.writes);
+        for (jump, count) in &other.loops {
+            *self.loops.entry(*jump).or_default() += count;
+        }
+    }
+
+    /// The total amount of executed instructions.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Executions per opcode mnemonic, most executed first.
+    pub fn opcodes(&self) -> Vec<(&'static str, u64)> {
+        let mut opcodes = self
+            .opcodes
+            .iter()
+            .map(|(&mnemonic, &count)| (mnemonic, count))
+            .collect::<Vec<_>>();
+        opcodes.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        opcodes
+    }
+
+    /// How often the instruction at `address` was executed.
+    pub fn executed(&self, address: usize) -> u64 {
+        self.executed.get(&address).copied().unwrap_or(0)
+    }
+
+    /// The `count` most executed instruction addresses.
+    pub fn hot_addresses(&self, count: usize) -> Vec<(usize, u64)> {
+        let mut addresses = counted(&self.executed);
+        addresses.sort_by_key(|&(address, executed)| (std::cmp::Reverse(executed), address));
+        addresses.truncate(count);
+        addresses
+    }
+
+    /// The `count` loops with the most iterations.
+    pub fn hot_loops(&self, count: usize) -> Vec<HotLoop> {
+        let mut loops = self
+            .loops
+            .iter()
+            .map(|(&(end, start), &iterations)| HotLoop {
+                start,
+                end,
+                iterations,
+            })
+            .collect::<Vec<_>>();
+        loops.sort_by_key(|hot| (std::cmp::Reverse(hot.iterations), hot.start, hot.end));
+        loops.truncate(count);
+        loops
+    }
+
+    /// Every address that was read and how often.
+    pub fn reads(&self) -> Vec<(usize, u64)> {
+        counted(&self.reads)
+    }
+
+    /// Every address that was written and how often.
+    pub fn writes(&self) -> Vec<(usize, u64)> {
+        counted(&self.writes)
+    }
+
+    /// The instructions of the original program that never executed.
+    pub fn never_executed(&self) -> Vec<Line> {
+        disassemble(&self.program)
+            .into_iter()
+            .filter(|line| !line.text.starts_with("DATA") && self.executed(line.address) == 0)
+            .collect()
+    }
+
+    /// The report as a human readable table.
+    pub fn table(&self) -> String {
+        let mut table = String::new();
+        let instructions = self.instructions.max(1) as f64;
+        let _ = writeln!(table, "Intcode profile: {} instructions", self.instructions);
+
+        let _ = writeln!(table, "\nOpcode  {:>12}  {:>6}", "Executed", "Share");
+        for (mnemonic, count) in self.opcodes() {
+            let _ = writeln!(
+                table,
+                "{mnemonic:<6}  {count:>12}  {:>5.1}%",
+                count as f64 * 100.0 / instructions
+            );
+        }
+
+        let _ = writeln!(table, "\nAddress  {:>12}  Instruction", "Executed");
+        for (address, count) in self.hot_addresses(TOP) {
+            let _ = writeln!(
+                table,
+                "{address:04}     {count:>12}  {}",
+                self.instruction(address)
+            );
+        }
+
+        let _ = writeln!(table, "\nLoop        {:>12}", "Iterations");
+        for hot in self.hot_loops(TOP) {
+            let _ = writeln!(
+                table,
+                "{:04}..{:04}  {:>12}",
+                hot.start, hot.end, hot.iterations
+            );
+        }
+
+        let _ = writeln!(
+            table,
+            "\nMemory: {} cells read, {} cells written",
+            self.reads().len(),
+            self.writes().len()
+        );
+
+        let never = self.never_executed();
+        let total = disassemble(&self.program)
+            .iter()
+            .filter(|line| !line.text.starts_with("DATA"))
+            .count();
+        let _ = writeln!(
+            table,
+            "\nNever executed: {} of {total} instructions",
+            never.len()
+        );
+        for line in never {
+            let _ = writeln!(table, "{line}");
+        }
+        table
+    }
+
+    /// The report as JSON.
+    pub fn json(&self) -> JsonValue {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert(
+            "instructions".into(),
+            JsonValue::Number(self.instructions as f64),
+        );
+        map.insert(
+            "opcodes".into(),
+            JsonValue::Object(
+                self.opcodes
+                    .iter()
+                    .map(|(&mnemonic, &count)| {
+                        (mnemonic.to_string(), JsonValue::Number(count as f64))
+                    })
+                    .collect(),
+            ),
+        );
+        map.insert(
+            "addresses".into(),
+            JsonValue::Array(
+                counted(&self.executed)
+                    .into_iter()
+                    .map(|(address, count)| pair(address, count))
+                    .collect(),
+            ),
+        );
+        map.insert(
+            "loops".into(),
+            JsonValue::Array(
+                self.hot_loops(usize::MAX)
+                    .into_iter()
+                    .map(|hot| {
+                        let mut map = HashMap::new();
+                        map.insert("start".into(), JsonValue::Number(hot.start as f64));
+                        map.insert("end".into(), JsonValue::Number(hot.end as f64));
+                        map.insert(
+                            "iterations".into(),
+                            JsonValue::Number(hot.iterations as f64),
+                        );
+                        JsonValue::Object(map)
+                    })
+                    .collect(),
+            ),
+        );
+        map.insert(
+            "reads".into(),
+            JsonValue::Array(
+                self.reads()
+                    .into_iter()
+                    .map(|(address, count)| pair(address, count))
+                    .collect(),
+            ),
+        );
+        map.insert(
+            "writes".into(),
+            JsonValue::Array(
+                self.writes()
+                    .into_iter()
+                    .map(|(address, count)| pair(address, count))
+                    .collect(),
+            ),
+        );
+        map.insert(
+            "never_executed".into(),
+            JsonValue::Array(
+                self.never_executed()
+                    .into_iter()
+                    .map(|line| {
+                        let mut map = HashMap::new();
+                        map.insert("address".into(), JsonValue::Number(line.address as f64));
+                        map.insert("instruction".into(), JsonValue::String(line.text));
+                        JsonValue::Object(map)
+                    })
+                    .collect(),
+            ),
+        );
+        JsonValue::Object(map)
+    }
+
+    /// The disassembled instruction at `address` of the original program.
+    fn instruction(&self, address: usize) -> String {
+        disassemble_at(&self.program, address).map_or("?".to_string(), |line| line.text)
+    }
+}
+
+/// Whether profiling is enabled through the environment, see [`PROFILE_VAR`] and [`PROFILE_JSON_VAR`],
+/// and not paused.
+pub fn enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    let enabled = *ENABLED.get_or_init(|| {
+        env::var_os(PROFILE_VAR).is_some() || env::var_os(PROFILE_JSON_VAR).is_some()
+    });
+    enabled && !PAUSED.load(Ordering::Relaxed)
+}
+
+/// Stops profiling new computers and collecting dropped ones while `paused`, e.g. while benchmark
+/// samples run that nobody reads the profile of.
+pub fn pause(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Adds the profile of a dropped computer to the collected profile.
+pub(crate) fn collect(profile: &Profile) {
+    let mut collected = COLLECTED.lock().unwrap_or_else(|error| error.into_inner());
+    collected
+        .get_or_insert_with(Profile::default)
+        .merge(profile);
+}
+
+/// Takes the profile collected from every computer dropped since the last call.
+pub fn take() -> Option<Profile> {
+    COLLECTED
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .take()
+}
+
+/// Prints and/or stores `profile` as requested through the environment, `name` identifies the JSON file.
+pub fn report(profile: &Profile, name: &str) {
+    if env::var_os(PROFILE_VAR).is_some() {
+        println!("{}", profile.table());
+    }
+    if let Some(directory) = env::var_os(PROFILE_JSON_VAR) {
+        let path = Path::new(&directory).join(format!("{name}.json"));
+        let json = profile.json().format().unwrap_or_default();
+        match fs::create_dir_all(&directory).and_then(|_| fs::write(&path, json)) {
+            Ok(()) => println!("Intcode profile written to {}", path.display()),
+            Err(e) => eprintln!("failed to write Intcode profile to {}: {e}", path.display()),
+        }
+    }
+}
+
+fn increment(counts: &mut BTreeMap<usize, u64>, address: usize) {
+    *counts.entry(address).or_default() += 1;
+}
+
+fn add(counts: &mut BTreeMap<usize, u64>, other: &BTreeMap<usize, u64>) {
+    for (&address, count) in other {
+        *counts.entry(address).or_default() += count;
+    }
+}
+
+/// The counts with their address, by address.
+fn counted(counts: &BTreeMap<usize, u64>) -> Vec<(usize, u64)> {
+    counts
+        .iter()
+        .map(|(&address, &count)| (address, count))
+        .collect()
+}
+
+/// An `{"address": .., "count": ..}` object.
+fn pair(address: usize, count: u64) -> JsonValue {
+    let mut map = HashMap::new();
+    map.insert("address".into(), JsonValue::Number(address as f64));
+    map.insert("count".into(), JsonValue::Number(count as f64));
+    JsonValue::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::IntCodeComputer;
+
+    /// Counts down from 3 and outputs every value, the last instruction is never reached.
+    const PROGRAM: &str = "4,14,1001,14,-1,14,1005,14,0,99,1101,0,0,13,3";
+
+    fn profiled() -> IntCodeComputer {
+        let mut computer = IntCodeComputer::new(PROGRAM);
+        computer.set_profiling(true);
+        computer.run().unwrap();
+        computer
+    }
+
+    #[test]
+    fn test_counts() {
+        let computer = profiled();
+        let profile = computer.profile().unwrap();
+        assert_eq!(computer.output(), &[3, 2, 1]);
+        // 3 iterations of OUT, ADD and JNZ, followed by HLT
+        assert_eq!(profile.instructions(), 10);
+        assert_eq!(
+            profile.opcodes(),
+            vec![("ADD", 3), ("JNZ", 3), ("OUT", 3), ("HLT", 1)]
+        );
+        assert_eq!(profile.executed(6), 3);
+        assert_eq!(profile.reads(), vec![(14, 9)]);
+        assert_eq!(profile.writes(), vec![(14, 3)]);
+        assert_eq!(
+            profile.hot_loops(5),
+            vec![super::HotLoop {
+                start: 0,
+                end: 6,
+                iterations: 2
+            }]
+        );
+        let never = profile
+            .never_executed()
+            .into_iter()
+            .map(|line| line.address)
+            .collect::<Vec<_>>();
+        assert_eq!(never, vec![10]);
+    }
+
+    #[test]
+    fn test_merge_and_report() {
+        let mut profile = profiled().profile().unwrap().clone();
+        profile.merge(&profiled().profile().unwrap().clone());
+        assert_eq!(profile.instructions(), 20);
+        assert!(profile.table().contains("0000..0006             4"));
+        let json = profile.json().stringify().unwrap();
+        assert!(json.contains("\"instructions\":20"));
+    }
+
+    #[test]
+    fn test_clones_share_the_program() {
+        let computer = profiled();
+        let clone = computer.clone();
+        let (profile, cloned) = (computer.profile().unwrap(), clone.profile().unwrap());
+        assert_eq!(cloned.instructions(), 0);
+        assert!(std::sync::Arc::ptr_eq(&profile.program, &cloned.program));
+    }
+
+    #[test]
+    fn test_far_addresses() {
+        // Writes and outputs a cell a trillion cells past the program
+        let mut computer = IntCodeComputer::new("109,1000000000000,21101,3,4,7,204,7,99");
+        computer.set_profiling(true);
+        assert_eq!(computer.run().unwrap(), &[7]);
+        let profile = computer.profile().unwrap();
+        assert_eq!(profile.writes(), vec![(1_000_000_000_007, 1)]);
+        assert_eq!(profile.reads(), vec![(1_000_000_000_007, 1)]);
+    }
+}
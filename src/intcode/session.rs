@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+use tinyjson::JsonValue;
+
+use crate::intcode::{IntCodeComputer, Memory, MemoryKind};
+
+/// Saving and loading the state of a computer as JSON, e.g. to continue a long interactive session
+/// later or to attach the state of a failing computer to a bug report.
+///
+/// Intcode values and counters are stored as text like the puzzle inputs, as JSON numbers can not
+/// hold every 64 bit value. Memory is stored as `address=value` pairs of the cells that are not 0, so
+/// sparse memory is saved without walking every address up to its end. Breakpoints, loop detection
+/// and profiles are not part of a session.
+impl IntCodeComputer {
+    /// Writes the memory, instruction pointer, relative base, queued input, output, budget and executed
+    /// instruction count to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let json = JsonValue::from(self);
+        let mut file = fs::File::create(path)?;
+        json.format_to(&mut file)
+    }
+
+    /// Loads a computer saved with [`IntCodeComputer::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let json = JsonValue::from_str(&json).or(Err("not a valid JSON file."))?;
+        IntCodeComputer::try_from(&json)
+    }
+}
+
+impl From<&IntCodeComputer> for JsonValue {
+    fn from(value: &IntCodeComputer) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        let memory = value.memory();
+
+        let cells = memory
+            .cells()
+            .into_iter()
+            .map(|(address, value)| format!("{address}={value}"))
+            .collect::<Vec<_>>();
+        map.insert("memory".into(), JsonValue::String(cells.join(",")));
+        map.insert("length".into(), JsonValue::String(memory.len().to_string()));
+        map.insert(
+            "memory_kind".into(),
+            JsonValue::String(kind_name(memory.kind()).into()),
+        );
+        map.insert("sparse".into(), JsonValue::Boolean(memory.is_sparse()));
+        map.insert(
+            "instruction_pointer".into(),
+            JsonValue::String(value.instruction_pointer().to_string()),
+        );
+        map.insert(
+            "relative_base".into(),
+            JsonValue::String(value.relative_base().to_string()),
+        );
+        map.insert("input".into(), values(value.pending_input().copied()));
+        map.insert("output".into(), values(value.output().iter().copied()));
+        map.insert(
+            "budget".into(),
+            match value.budget() {
+                Some(budget) => JsonValue::String(budget.to_string()),
+                None => JsonValue::Null,
+            },
+        );
+        map.insert(
+            "executed".into(),
+            JsonValue::String(value.executed().to_string()),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for IntCodeComputer {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected session to be a JSON object.")?;
+
+        let text = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<String>())
+                .ok_or(format!("Expected session.{key} to be a string."))
+        };
+        let number = |key: &str| {
+            text(key)?
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| format!("Expected session.{key} to be an unsigned integer: {e}"))
+        };
+        let parse = |key: &str| {
+            let text = text(key)?;
+            text.split(',')
+                .filter(|value| !value.is_empty())
+                .map(|value| value.trim().parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| {
+                    format!("Expected session.{key} to hold comma separated integers: {e}")
+                })
+        };
+
+        let cells = text("memory")?
+            .split(',')
+            .filter(|cell| !cell.is_empty())
+            .map(|cell| {
+                let (address, value) = cell.split_once('=')?;
+                Some((
+                    address.trim().parse::<usize>().ok()?,
+                    value.trim().parse::<i64>().ok()?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or("Expected session.memory to hold comma separated address=value pairs.")?;
+        let length = number("length")? as usize;
+        if length == 0 {
+            return Err("Expected session.memory to hold a program.".into());
+        }
+        let kind = match text("memory_kind")?.as_str() {
+            "auto" => MemoryKind::Auto,
+            "dense" => MemoryKind::Dense,
+            "sparse" => MemoryKind::Sparse,
+            kind => return Err(format!("Unknown session.memory_kind \"{kind}\".")),
+        };
+        let sparse = json
+            .get("sparse")
+            .and_then(|v| v.get::<bool>().copied())
+            .ok_or("Expected session.sparse to be a boolean.")?;
+        let memory = Memory::from_cells(&cells, length, kind, sparse).map_err(|address| {
+            format!(
+                "Expected session.memory to end before session.length, found address {address}."
+            )
+        })?;
+
+        let relative_base = text("relative_base")?
+            .parse::<i64>()
+            .map_err(|e| format!("Expected session.relative_base to be an integer: {e}"))?;
+        let budget = json
+            .get("budget")
+            .map(|v| {
+                if v.is_null() {
+                    Ok(None)
+                } else {
+                    number("budget").map(Some)
+                }
+            })
+            .ok_or("Expected session.budget to be null or a string.")??;
+
+        let mut computer = IntCodeComputer::from_memory(memory);
+        computer.set_relative_base(relative_base);
+        parse("input")?
+            .into_iter()
+            .for_each(|value| computer.push_input(value));
+        computer.set_budget(budget);
+        computer.resume_session(
+            number("instruction_pointer")? as usize,
+            parse("output")?,
+            number("executed")?,
+        );
+        Ok(computer)
+    }
+}
+
+fn kind_name(kind: MemoryKind) -> &'static str {
+    match kind {
+        MemoryKind::Auto => "auto",
+        MemoryKind::Dense => "dense",
+        MemoryKind::Sparse => "sparse",
+    }
+}
+
+/// Comma separated values, like a puzzle input.
+fn values(values: impl Iterator<Item = i64>) -> JsonValue {
+    JsonValue::String(
+        values
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::intcode::RunState;
+
+    #[test]
+    fn test_round_trip() {
+        // Outputs the sum of two inputs, then a value that does not fit in a JSON number
+        let mut computer =
+            IntCodeComputer::new("3,13,3,14,1,13,14,15,4,15,104,9007199254740993,99,0,0,0");
+        computer.push_input(3);
+        computer.set_budget(Some(100));
+        assert_eq!(computer.resume(), Ok(RunState::NeedsInput));
+        computer.set_relative_base(-7);
+
+        let path = env::temp_dir().join(format!("intcode-session-{}.json", std::process::id()));
+        computer.save(&path).unwrap();
+        let mut loaded = IntCodeComputer::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.memory(), computer.memory());
+        assert_eq!(loaded.instruction_pointer(), computer.instruction_pointer());
+        assert_eq!(loaded.relative_base(), -7);
+        assert_eq!(loaded.executed(), computer.executed());
+
+        for computer in [&mut computer, &mut loaded] {
+            computer.push_input(4);
+            assert_eq!(computer.run(), Ok(&[7, 9007199254740993][..]));
+        }
+        assert_eq!(loaded.executed(), computer.executed());
+        assert_eq!(loaded.budget(), computer.budget());
+    }
+
+    #[test]
+    fn test_sparse_round_trip() {
+        // Writes a cell a trillion cells past the program
+        let mut computer = IntCodeComputer::new("109,1000000000000,21101,3,4,7,99");
+        computer.set_memory_kind(MemoryKind::Sparse);
+        computer.run().unwrap();
+        computer.set_budget(Some(9_007_199_254_740_993));
+
+        let json = JsonValue::from(&computer);
+        let loaded = IntCodeComputer::try_from(&json).unwrap();
+        assert_eq!(loaded.memory(), computer.memory());
+        assert!(loaded.memory().is_sparse());
+        assert_eq!(loaded.memory().kind(), MemoryKind::Sparse);
+        assert_eq!(loaded.memory().len(), 1_000_000_000_008);
+        assert_eq!(loaded.budget(), Some(9_007_199_254_740_993));
+    }
+
+    #[test]
+    fn test_invalid_sessions() {
+        let session = |json: &str| IntCodeComputer::try_from(&JsonValue::from_str(json).unwrap());
+        assert!(session("[]").is_err());
+        assert!(session(r#"{"memory": "0=1,1=x"}"#).is_err());
+        assert!(session(
+            r#"{"memory": "1=0,7=2", "length": "5", "memory_kind": "auto", "sparse": false}"#
+        )
+        .is_err());
+
+        // A huge length is loaded sparsely instead of allocating every cell
+        let huge = r#"{"memory": "0=99", "length": "1000000000000", "memory_kind": "dense", "sparse": false, "instruction_pointer": "0",
+                       "relative_base": "0", "input": "", "output": "", "budget": null, "executed": "0"}"#;
+        let computer = session(huge).unwrap();
+        assert!(computer.memory().is_sparse());
+        assert_eq!(computer.memory().len(), 1_000_000_000_000);
+        let valid = r#"{"memory": "0=1,4=99", "length": "5", "memory_kind": "auto", "sparse": false, "instruction_pointer": "0",
+                        "relative_base": "0", "input": "", "output": "", "budget": null, "executed": "0"}"#;
+        assert_eq!(session(valid).unwrap().run(), Ok(&[][..]));
+    }
+}
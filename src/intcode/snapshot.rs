@@ -0,0 +1,134 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::intcode::memory::SparseMemory;
+
+/// The amount of memory cells per copy-on-write page.
+pub(crate) const PAGE_SIZE: usize = 256;
+
+pub(crate) type Page = Arc<[i64; PAGE_SIZE]>;
+
+/// The complete state of an [`IntCodeComputer`], taken with [`IntCodeComputer::snapshot`].
+///
+/// Memory is stored in pages that are shared with every other snapshot of the same computer (and its
+/// clones) until a page is written, so thousands of branches only cost the pages they changed.
+/// Sparse memory already consists of such pages and is shared as it is.
+///
+/// [`IntCodeComputer`]: crate::intcode::IntCodeComputer
+/// [`IntCodeComputer::snapshot`]: crate::intcode::IntCodeComputer::snapshot
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub(crate) memory: Pages,
+    pub(crate) instruction_pointer: usize,
+    pub(crate) relative_base: i64,
+    pub(crate) input: VecDeque<i64>,
+    pub(crate) output: Vec<i64>,
+    pub(crate) budget: Option<u64>,
+    pub(crate) executed: u64,
+}
+
+impl Snapshot {
+    /// The amount of memory pages this snapshot shares with `other`.
+    pub fn shared_pages(&self, other: &Snapshot) -> usize {
+        match (&self.memory, &other.memory) {
+            (Pages::Dense { pages: a, .. }, Pages::Dense { pages: b, .. }) => {
+                a.iter().zip(b).filter(|(a, b)| Arc::ptr_eq(a, b)).count()
+            }
+            (Pages::Sparse(a), Pages::Sparse(b)) => {
+                let b = b.pages().into_iter().collect::<HashMap<_, _>>();
+                a.pages()
+                    .into_iter()
+                    .filter(|(index, a)| b.get(index).is_some_and(|b| Arc::ptr_eq(a, b)))
+                    .count()
+            }
+            _ => 0,
+        }
+    }
+
+    /// The memory cell at `address`, 0 beyond the stored memory.
+    pub fn get(&self, address: usize) -> i64 {
+        match &self.memory {
+            Pages::Dense { length, .. } if address >= *length => 0,
+            Pages::Dense { pages, .. } => pages[address / PAGE_SIZE][address % PAGE_SIZE],
+            Pages::Sparse(sparse) => sparse.get(address),
+        }
+    }
+}
+
+/// The memory of a [`Snapshot`], in the form of the backend it was taken from.
+#[derive(Clone, Debug)]
+pub(crate) enum Pages {
+    /// Dense memory, split into pages by the [`PageTracker`].
+    Dense {
+        pages: Vec<Page>,
+        length: usize,
+    },
+    Sparse(SparseMemory),
+}
+
+/// Tracks which pages of the live memory changed since the last snapshot or restore.
+#[derive(Clone, Debug)]
+pub(crate) struct PageTracker {
+    /// The pages the live memory was last synchronized with.
+    base: Vec<Page>,
+    dirty: Vec<bool>,
+}
+
+impl PageTracker {
+    pub(crate) fn new(memory: &[i64]) -> Self {
+        let base = memory.chunks(PAGE_SIZE).map(page).collect::<Vec<_>>();
+        let dirty = vec![false; base.len()];
+        Self { base, dirty }
+    }
+
+    /// Marks the page holding `address` as changed.
+    pub(crate) fn write(&mut self, address: usize) {
+        let index = address / PAGE_SIZE;
+        if index >= self.dirty.len() {
+            self.dirty.resize(index + 1, true);
+        }
+        self.dirty[index] = true;
+    }
+
+    /// Brings the shared pages up to date with the live memory and returns them.
+    pub(crate) fn sync(&mut self, memory: &[i64]) -> Vec<Page> {
+        self.base.truncate(memory.len().div_ceil(PAGE_SIZE));
+        for (index, chunk) in memory.chunks(PAGE_SIZE).enumerate() {
+            if index >= self.base.len() {
+                self.base.push(page(chunk));
+            } else if self.dirty.get(index).copied().unwrap_or(true) {
+                self.base[index] = page(chunk);
+            }
+        }
+        self.dirty = vec![false; self.base.len()];
+        self.base.clone()
+    }
+
+    /// Restores the live memory to the pages of a snapshot, only copying the pages that differ.
+    pub(crate) fn restore(&mut self, memory: &mut Vec<i64>, pages: &[Page], length: usize) {
+        memory.resize(length, 0);
+        for (index, page) in pages.iter().enumerate() {
+            let unchanged = self
+                .base
+                .get(index)
+                .is_some_and(|base| Arc::ptr_eq(base, page))
+                && !self.dirty.get(index).copied().unwrap_or(true);
+            if unchanged {
+                continue;
+            }
+
+            let start = index * PAGE_SIZE;
+            let end = (start + PAGE_SIZE).min(length);
+            memory[start..end].copy_from_slice(&page[..end - start]);
+        }
+        self.base = pages.to_vec();
+        self.dirty = vec![false; self.base.len()];
+    }
+}
+
+/// Copies a chunk of memory into a new page, padded with zeroes.
+fn page(chunk: &[i64]) -> Page {
+    let mut page = [0; PAGE_SIZE];
+    page[..chunk.len()].copy_from_slice(chunk);
+    Arc::new(page)
+}
@@ -0,0 +1,852 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::intcode::ascii::AsciiComputer;
+use crate::intcode::{IntCodeComputer, IntcodeError};
+
+/// The most instructions the springdroid accepts.
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+/// A springdroid register: the ground sensors `A` to `I`, the temporary register `T` and the jump register `J`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Register {
+    /// Whether there is ground `n + 1` tiles ahead, `Sensor(0)` is `A`.
+    Sensor(u8),
+    T,
+    J,
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Register::Sensor(sensor) => write!(f, "{}", (b'A' + sensor) as char),
+            Register::T => write!(f, "T"),
+            Register::J => write!(f, "J"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    And,
+    Or,
+    Not,
+}
+
+/// A springscript instruction, `y` is always `T` or `J`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub op: Op,
+    pub x: Register,
+    pub y: Register,
+}
+
+impl Instruction {
+    fn new(op: Op, x: Register, y: Register) -> Self {
+        Self { op, x, y }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        write!(f, "{op} {} {}", self.x, self.y)
+    }
+}
+
+/// Walking only senses `A` to `D` (part one), running senses `A` to `I` (part two).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    /// The amount of ground sensors.
+    pub fn sensors(self) -> u8 {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+/// Everything that can go wrong while compiling springscript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpringscriptError {
+    /// The expression is malformed, `position` is the offset of the offending character.
+    Parse { position: usize, message: String },
+    /// The expression reads a sensor the mode does not have, e.g. `E` while walking.
+    SensorOutOfRange { sensor: char, mode: Mode },
+    /// Even the shortest script is longer than [`MAX_INSTRUCTIONS`], `instructions` is its length or,
+    /// for expressions far too large to allocate, a lower bound.
+    TooLong { instructions: usize },
+    /// No candidate could be allocated onto the two writable registers.
+    NoAllocation,
+}
+
+impl Error for SpringscriptError {}
+
+impl Display for SpringscriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpringscriptError::Parse { position, message } => write!(f, "Springscript: {message} at {position}"),
+            SpringscriptError::SensorOutOfRange { sensor, mode } => {
+                write!(f, "Springscript: sensor {sensor} is not available in {mode} mode")
+            }
+            SpringscriptError::TooLong { instructions } => write!(
+                f,
+                "Springscript: the script needs at least {instructions} instructions, at most {MAX_INSTRUCTIONS} are allowed"
+            ),
+            SpringscriptError::NoAllocation => write!(f, "Springscript: the expression does not fit the registers J and T"),
+        }
+    }
+}
+
+/// A boolean expression over the ground sensors, e.g. `!(A & B & C) & D`.
+///
+/// `!` binds tighter than `&`, which binds tighter than `|`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Sensor(u8),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression, bit `n` of `ground` is sensor `n`.
+    pub fn eval(&self, ground: u16) -> bool {
+        match self {
+            Expr::Sensor(sensor) => ground & (1 << sensor) != 0,
+            Expr::Not(expr) => !expr.eval(ground),
+            Expr::And(left, right) => left.eval(ground) && right.eval(ground),
+            Expr::Or(left, right) => left.eval(ground) || right.eval(ground),
+        }
+    }
+
+    /// The sensors the expression reads, as a bit set.
+    pub fn sensors(&self) -> u16 {
+        match self {
+            Expr::Sensor(sensor) => 1 << sensor,
+            Expr::Not(expr) => expr.sensors(),
+            Expr::And(left, right) | Expr::Or(left, right) => left.sensors() | right.sensors(),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = SpringscriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s
+                .char_indices()
+                .filter(|(_, c)| !c.is_whitespace())
+                .collect(),
+            next: 0,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((position, c)) => Err(parser.error(position, format!("unexpected '{c}'"))),
+        }
+    }
+}
+
+/// A recursive descent parser over the non-whitespace characters of an expression.
+struct Parser {
+    chars: Vec<(usize, char)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, char)> {
+        self.chars.get(self.next).copied()
+    }
+
+    fn error(&self, position: usize, message: String) -> SpringscriptError {
+        SpringscriptError::Parse { position, message }
+    }
+
+    fn or(&mut self) -> Result<Expr, SpringscriptError> {
+        let mut expr = self.and()?;
+        while let Some((_, '|')) = self.peek() {
+            self.next += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, SpringscriptError> {
+        let mut expr = self.not()?;
+        while let Some((_, '&')) = self.peek() {
+            self.next += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, SpringscriptError> {
+        let Some((position, c)) = self.peek() else {
+            let end = self
+                .chars
+                .last()
+                .map_or(0, |&(position, c)| position + c.len_utf8());
+            return Err(self.error(end, "unexpected end of expression".into()));
+        };
+        self.next += 1;
+        match c {
+            '!' => Ok(Expr::Not(Box::new(self.not()?))),
+            '(' => {
+                let expr = self.or()?;
+                match self.peek() {
+                    Some((_, ')')) => {
+                        self.next += 1;
+                        Ok(expr)
+                    }
+                    _ => Err(self.error(position, "unclosed '('".into())),
+                }
+            }
+            'A'..='I' => Ok(Expr::Sensor(c as u8 - b'A')),
+            _ => Err(self.error(
+                position,
+                format!("expected a sensor A-I, '!' or '(' but found '{c}'"),
+            )),
+        }
+    }
+}
+
+/// A compiled springscript program, ready to be sent to the springdroid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+/// How the springdroid did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// It made it across the hull, reporting the amount of hull damage.
+    Damage(i64),
+    /// It fell into space, with the animation of its last moments.
+    Fell(String),
+}
+
+impl Script {
+    /// Simulates the script, bit `n` of `ground` is sensor `n`. Returns whether the springdroid jumps.
+    pub fn jumps(&self, ground: u16) -> bool {
+        let (mut t, mut j) = (false, false);
+        for instruction in &self.instructions {
+            let x = match instruction.x {
+                Register::Sensor(sensor) => ground & (1 << sensor) != 0,
+                Register::T => t,
+                Register::J => j,
+            };
+            let y = if instruction.y == Register::T {
+                &mut t
+            } else {
+                &mut j
+            };
+            *y = match instruction.op {
+                Op::And => x && *y,
+                Op::Or => x || *y,
+                Op::Not => !x,
+            };
+        }
+        j
+    }
+
+    /// Checks the script against the truth table of `expr` over every sensor reading of the mode,
+    /// returns the first reading where they disagree.
+    pub fn verify(&self, expr: &Expr) -> Result<(), u16> {
+        match (0..1 << self.mode.sensors()).find(|&ground| self.jumps(ground) != expr.eval(ground))
+        {
+            Some(ground) => Err(ground),
+            None => Ok(()),
+        }
+    }
+
+    /// Sends the script to the springdroid program over ASCII and lets it walk or run.
+    pub fn run(&self, computer: IntCodeComputer) -> Result<Outcome, IntcodeError> {
+        let mut computer = AsciiComputer::new(computer);
+        computer.run()?;
+        for instruction in &self.instructions {
+            computer.send_line(&instruction.to_string());
+        }
+        computer.send_line(&self.mode.to_string());
+
+        let output = computer.run()?;
+        match output.numbers.first() {
+            Some(&damage) => Ok(Outcome::Damage(damage)),
+            None => Ok(Outcome::Fell(output.text)),
+        }
+    }
+}
+
+/// The instructions followed by the mode, one per line, as typed into the springdroid.
+impl Display for Script {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{instruction}")?;
+        }
+        writeln!(f, "{}", self.mode)
+    }
+}
+
+/// Compiles an expression into the shortest springscript this compiler finds that jumps exactly when
+/// the expression is true.
+///
+/// The expression is minimized with Quine-McCluskey into a sum of products and, through its complement,
+/// a product of sums. Every candidate, including the expression as written, is then allocated onto the
+/// two writable registers and the shortest result wins.
+pub fn compile(expr: &Expr, mode: Mode) -> Result<Script, SpringscriptError> {
+    let sensors = expr.sensors();
+    if let Some(sensor) = (mode.sensors()..9).find(|sensor| sensors & (1 << sensor) != 0) {
+        return Err(SpringscriptError::SensorOutOfRange {
+            sensor: (b'A' + sensor) as char,
+            mode,
+        });
+    }
+
+    let variables = (0..9)
+        .filter(|sensor| sensors & (1 << sensor) != 0)
+        .collect::<Vec<u8>>();
+    let candidates = [
+        minimize(expr, &variables, true),
+        minimize(expr, &variables, false).complement(),
+        Node::from_expr(expr, false),
+    ];
+
+    // Every literal takes at least one instruction, which rules out huge candidates before allocating them
+    let shortest = candidates.iter().map(Node::literals).min().unwrap();
+    if shortest > MAX_INSTRUCTIONS {
+        return Err(SpringscriptError::TooLong {
+            instructions: shortest,
+        });
+    }
+
+    // J and T are both false when the script starts
+    let mut allocator = Allocator::default();
+    let instructions = candidates
+        .iter()
+        .filter(|node| node.literals() <= MAX_INSTRUCTIONS)
+        .filter_map(|node| allocator.generate(node, Register::J, Some(Register::T), true))
+        .min_by_key(Vec::len)
+        .ok_or(SpringscriptError::NoAllocation)?;
+
+    if instructions.len() > MAX_INSTRUCTIONS {
+        return Err(SpringscriptError::TooLong {
+            instructions: instructions.len(),
+        });
+    }
+    Ok(Script { instructions, mode })
+}
+
+/// An expression in negation normal form, only sensors are negated.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    Literal { sensor: u8, negated: bool },
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    True,
+    False,
+}
+
+impl Node {
+    fn from_expr(expr: &Expr, negated: bool) -> Self {
+        match (expr, negated) {
+            (Expr::Sensor(sensor), _) => Node::Literal {
+                sensor: *sensor,
+                negated,
+            },
+            (Expr::Not(expr), _) => Node::from_expr(expr, !negated),
+            (Expr::And(left, right), false) | (Expr::Or(left, right), true) => Node::And(vec![
+                Node::from_expr(left, negated),
+                Node::from_expr(right, negated),
+            ])
+            .flatten(),
+            (Expr::Or(left, right), false) | (Expr::And(left, right), true) => Node::Or(vec![
+                Node::from_expr(left, negated),
+                Node::from_expr(right, negated),
+            ])
+            .flatten(),
+        }
+    }
+
+    /// Merges nested operations of the same kind and unwraps operations with a single operand.
+    fn flatten(self) -> Self {
+        match self {
+            Node::And(nodes) | Node::Or(nodes) if nodes.len() == 1 => {
+                nodes.into_iter().next().unwrap()
+            }
+            Node::And(nodes) => Node::And(
+                nodes
+                    .into_iter()
+                    .flat_map(|node| {
+                        if let Node::And(nodes) = node {
+                            nodes
+                        } else {
+                            vec![node]
+                        }
+                    })
+                    .collect(),
+            ),
+            Node::Or(nodes) => Node::Or(
+                nodes
+                    .into_iter()
+                    .flat_map(|node| {
+                        if let Node::Or(nodes) = node {
+                            nodes
+                        } else {
+                            vec![node]
+                        }
+                    })
+                    .collect(),
+            ),
+            node => node,
+        }
+    }
+
+    /// The amount of sensor reads.
+    fn literals(&self) -> usize {
+        match self {
+            Node::Literal { .. } => 1,
+            Node::And(nodes) | Node::Or(nodes) => nodes.iter().map(Node::literals).sum(),
+            Node::True | Node::False => 0,
+        }
+    }
+
+    /// The negation, by De Morgan's laws.
+    fn complement(&self) -> Self {
+        match self {
+            Node::Literal { sensor, negated } => Node::Literal {
+                sensor: *sensor,
+                negated: !negated,
+            },
+            Node::And(nodes) => Node::Or(nodes.iter().map(Node::complement).collect()),
+            Node::Or(nodes) => Node::And(nodes.iter().map(Node::complement).collect()),
+            Node::True => Node::False,
+            Node::False => Node::True,
+        }
+    }
+}
+
+/// A product term over the minimized variables: the bits in `free` do not matter, the others must equal `value`.
+type Implicant = (u16, u16);
+
+fn covers(&(value, free): &Implicant, minterm: u16) -> bool {
+    minterm & !free == value
+}
+
+/// The minimal sum of products of `expr` (or of its complement when `polarity` is false) over `variables`,
+/// found with Quine-McCluskey and a greedy cover of the prime implicants.
+fn minimize(expr: &Expr, variables: &[u8], polarity: bool) -> Node {
+    let ground = |minterm: u16| {
+        variables
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| minterm & (1 << bit) != 0)
+            .fold(0, |ground, (_, &sensor)| ground | 1 << sensor)
+    };
+    let minterms = (0..1u16 << variables.len())
+        .filter(|&minterm| expr.eval(ground(minterm)) == polarity)
+        .collect::<Vec<_>>();
+
+    // Merge implicants that differ in a single bit until nothing merges anymore
+    let mut primes = vec![];
+    let mut current = minterms
+        .iter()
+        .map(|&minterm| (minterm, 0))
+        .collect::<HashSet<Implicant>>();
+    while !current.is_empty() {
+        let implicants = current.into_iter().collect::<Vec<_>>();
+        let mut merged = HashSet::new();
+        let mut next = HashSet::new();
+        for (i, a) in implicants.iter().enumerate() {
+            for b in &implicants[i + 1..] {
+                let difference = a.0 ^ b.0;
+                if a.1 == b.1 && difference.count_ones() == 1 {
+                    next.insert((a.0 & b.0, a.1 | difference));
+                    merged.insert(*a);
+                    merged.insert(*b);
+                }
+            }
+        }
+        primes.extend(
+            implicants
+                .into_iter()
+                .filter(|implicant| !merged.contains(implicant)),
+        );
+        current = next;
+    }
+    primes.sort_unstable();
+
+    // Pick the implicant covering the most remaining minterms, preferring the ones with fewer literals
+    let mut uncovered = minterms;
+    let mut cover = vec![];
+    while !uncovered.is_empty() {
+        let best = *primes
+            .iter()
+            .max_by_key(|implicant| {
+                (
+                    uncovered
+                        .iter()
+                        .filter(|&&minterm| covers(implicant, minterm))
+                        .count(),
+                    implicant.1.count_ones(),
+                )
+            })
+            .unwrap();
+        uncovered.retain(|&minterm| !covers(&best, minterm));
+        cover.push(best);
+    }
+
+    let terms = cover
+        .into_iter()
+        .map(|(value, free)| {
+            let literals = variables
+                .iter()
+                .enumerate()
+                .filter(|&(bit, _)| free & (1 << bit) == 0)
+                .map(|(bit, &sensor)| Node::Literal {
+                    sensor,
+                    negated: value & (1 << bit) == 0,
+                })
+                .collect::<Vec<_>>();
+            if literals.is_empty() {
+                Node::True
+            } else {
+                Node::And(literals).flatten()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match terms.as_slice() {
+        [] => Node::False,
+        [.., Node::True] | [Node::True, ..] => Node::True,
+        _ => Node::Or(terms).flatten(),
+    }
+}
+
+/// The node, target register, scratch register and whether the target is known to be false.
+type Allocation = (Node, Register, Option<Register>, bool);
+
+/// Allocates expressions onto the two writable registers, remembering the code of every sub expression
+/// as the same operands are tried in many orders.
+#[derive(Default)]
+struct Allocator {
+    cache: HashMap<Allocation, Option<Vec<Instruction>>>,
+}
+
+impl Allocator {
+    /// The shortest code this allocator finds that leaves `node` in `target`, using `scratch` as second
+    /// register if given. `zero` tells whether `target` is known to be false, which saves an instruction
+    /// on the first write.
+    fn generate(
+        &mut self,
+        node: &Node,
+        target: Register,
+        scratch: Option<Register>,
+        zero: bool,
+    ) -> Option<Vec<Instruction>> {
+        let key = (node.clone(), target, scratch, zero);
+        if let Some(code) = self.cache.get(&key) {
+            return code.clone();
+        }
+
+        let direct = self.generate_direct(node, target, scratch, zero);
+        let negated = match node {
+            Node::And(_) | Node::Or(_) => self
+                .generate_direct(&node.complement(), target, scratch, zero)
+                .map(|mut code| {
+                    code.push(Instruction::new(Op::Not, target, target));
+                    code
+                }),
+            _ => None,
+        };
+        let code = [direct, negated].into_iter().flatten().min_by_key(Vec::len);
+        self.cache.insert(key, code.clone());
+        code
+    }
+
+    fn generate_direct(
+        &mut self,
+        node: &Node,
+        target: Register,
+        scratch: Option<Register>,
+        zero: bool,
+    ) -> Option<Vec<Instruction>> {
+        let not = |x| Instruction::new(Op::Not, x, target);
+        let any = Register::Sensor(0);
+        match *node {
+            Node::Literal {
+                sensor,
+                negated: true,
+            } => Some(vec![not(Register::Sensor(sensor))]),
+            Node::Literal {
+                sensor,
+                negated: false,
+            } if zero => Some(vec![Instruction::new(
+                Op::Or,
+                Register::Sensor(sensor),
+                target,
+            )]),
+            Node::Literal {
+                sensor,
+                negated: false,
+            } => Some(vec![not(Register::Sensor(sensor)), not(target)]),
+            Node::True if zero => Some(vec![not(target)]),
+            Node::True => Some(vec![not(any), Instruction::new(Op::Or, any, target)]),
+            Node::False if zero => Some(vec![]),
+            Node::False => Some(vec![not(any), Instruction::new(Op::And, any, target)]),
+            Node::And(ref nodes) => self.generate_operation(Op::And, nodes, target, scratch, zero),
+            Node::Or(ref nodes) => self.generate_operation(Op::Or, nodes, target, scratch, zero),
+        }
+    }
+
+    /// Computes one operand into `target` and combines every other operand into it, trying each operand first.
+    fn generate_operation(
+        &mut self,
+        op: Op,
+        nodes: &[Node],
+        target: Register,
+        scratch: Option<Register>,
+        zero: bool,
+    ) -> Option<Vec<Instruction>> {
+        let mut best: Option<Vec<Instruction>> = None;
+        'first: for first in 0..nodes.len() {
+            let Some(mut code) = self.generate(&nodes[first], target, scratch, zero) else {
+                continue;
+            };
+            for (_, node) in nodes.iter().enumerate().filter(|&(i, _)| i != first) {
+                match self.combine(op, node, target, scratch) {
+                    Some(combined) => code.extend(combined),
+                    None => continue 'first,
+                }
+            }
+            if best.as_ref().is_none_or(|best| code.len() < best.len()) {
+                best = Some(code);
+            }
+        }
+        best
+    }
+
+    /// Combines `node` into `target` with `op`, `None` when that needs a scratch register that is not available.
+    fn combine(
+        &mut self,
+        op: Op,
+        node: &Node,
+        target: Register,
+        scratch: Option<Register>,
+    ) -> Option<Vec<Instruction>> {
+        match (node, scratch) {
+            (
+                Node::Literal {
+                    sensor,
+                    negated: false,
+                },
+                _,
+            ) => Some(vec![Instruction::new(
+                op,
+                Register::Sensor(*sensor),
+                target,
+            )]),
+            (
+                Node::Literal {
+                    sensor,
+                    negated: true,
+                },
+                Some(scratch),
+            ) => Some(vec![
+                Instruction::new(Op::Not, Register::Sensor(*sensor), scratch),
+                Instruction::new(op, scratch, target),
+            ]),
+            // Without a scratch register: x AND !y is !(!x OR y), x OR !y is !(!x AND y)
+            (
+                Node::Literal {
+                    sensor,
+                    negated: true,
+                },
+                None,
+            ) => Some(vec![
+                Instruction::new(Op::Not, target, target),
+                Instruction::new(
+                    if op == Op::And { Op::Or } else { Op::And },
+                    Register::Sensor(*sensor),
+                    target,
+                ),
+                Instruction::new(Op::Not, target, target),
+            ]),
+            (node, Some(scratch)) => {
+                let mut code = self.generate(node, scratch, None, false)?;
+                code.push(Instruction::new(op, scratch, target));
+                Some(code)
+            }
+            (_, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile_str(expr: &str, mode: Mode) -> Result<Script, SpringscriptError> {
+        compile(&expr.parse().unwrap(), mode)
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr = "!(A & B) | C".parse::<Expr>().unwrap();
+        assert_eq!(expr.sensors(), 0b111);
+        assert!(expr.eval(0b000));
+        assert!(!expr.eval(0b011));
+        assert!(expr.eval(0b111));
+
+        assert!(matches!(
+            "A & ".parse::<Expr>(),
+            Err(SpringscriptError::Parse { position: 3, .. })
+        ));
+        assert!(matches!(
+            "(A | B".parse::<Expr>(),
+            Err(SpringscriptError::Parse { position: 0, .. })
+        ));
+        assert!(matches!(
+            "A T".parse::<Expr>(),
+            Err(SpringscriptError::Parse { position: 2, .. })
+        ));
+        assert!(matches!(
+            "J".parse::<Expr>(),
+            Err(SpringscriptError::Parse { position: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_walk() {
+        // Jump when there is a hole in the next three tiles and ground to land on
+        let expr = "!(A & B & C) & D".parse().unwrap();
+        let script = compile(&expr, Mode::Walk).unwrap();
+        assert_eq!(
+            script.to_string(),
+            "OR A J\nAND B J\nAND C J\nNOT J J\nAND D J\nWALK\n"
+        );
+        assert_eq!(script.verify(&expr), Ok(()));
+    }
+
+    #[test]
+    fn test_run() {
+        // Only jump when the springdroid can either walk on or jump again after landing
+        let expr = "!(A & B & C) & D & (E | H)".parse().unwrap();
+        let script = compile(&expr, Mode::Run).unwrap();
+        assert!(script.instructions.len() <= 10, "{script}");
+        assert_eq!(script.verify(&expr), Ok(()));
+    }
+
+    #[test]
+    fn test_minimize() {
+        assert_eq!(
+            compile_str("A & B | A & !B", Mode::Walk)
+                .unwrap()
+                .to_string(),
+            "OR A J\nWALK\n"
+        );
+        assert_eq!(
+            compile_str("A | !A", Mode::Walk).unwrap().to_string(),
+            "NOT J J\nWALK\n"
+        );
+        assert_eq!(
+            compile_str("A & !A", Mode::Walk).unwrap().to_string(),
+            "WALK\n"
+        );
+        assert_eq!(
+            compile_str("!!D", Mode::Walk).unwrap().to_string(),
+            "OR D J\nWALK\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mode = Mode::Walk;
+        assert_eq!(
+            compile_str("A | E", mode),
+            Err(SpringscriptError::SensorOutOfRange { sensor: 'E', mode })
+        );
+        // The parity of nine sensors needs far more than 15 instructions
+        let parity = "(A & !B | !A & B)".to_string();
+        let parity = ["C", "D", "E", "F", "G", "H", "I"]
+            .iter()
+            .fold(parity, |parity, sensor| {
+                format!("(({parity}) & !{sensor} | !({parity}) & {sensor})")
+            });
+        assert!(matches!(
+            compile_str(&parity, Mode::Run),
+            Err(SpringscriptError::TooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_every_function_of_three_sensors() {
+        let mut compiled = 0;
+        for table in 0..256u16 {
+            // Build the function from its minterms, the compiler has to find something shorter
+            let minterms = (0..8)
+                .filter(|minterm| table & (1 << minterm) != 0)
+                .map(|minterm: u16| {
+                    let literal = |bit: u16, sensor| {
+                        if minterm & (1 << bit) != 0 {
+                            sensor
+                        } else {
+                            format!("!{sensor}")
+                        }
+                    };
+                    format!(
+                        "({} & {} & {})",
+                        literal(0, "A".into()),
+                        literal(1, "B".into()),
+                        literal(2, "C".into())
+                    )
+                })
+                .collect::<Vec<_>>();
+            let source = if minterms.is_empty() {
+                "A & !A".to_string()
+            } else {
+                minterms.join(" | ")
+            };
+            let expr = source.parse().unwrap();
+
+            if let Ok(script) = compile(&expr, Mode::Walk) {
+                assert_eq!(script.verify(&expr), Ok(()), "{source}:\n{script}");
+                compiled += 1;
+            }
+        }
+        assert!(compiled > 250, "only {compiled} functions fit");
+    }
+
+    #[test]
+    fn test_springdroid() {
+        // Prompts with "?", reads input up to the first 'W' and reports 1000 plus the amount of characters read
+        let program =
+            "104,63,104,10,3,50,1001,51,1,51,1008,50,87,52,1006,52,4,1001,51,1000,53,4,53,99";
+        let script = compile_str("!A", Mode::Walk).unwrap();
+        assert_eq!(
+            script.run(IntCodeComputer::new(program)),
+            Ok(Outcome::Damage(1009))
+        );
+    }
+}
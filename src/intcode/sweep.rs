@@ -0,0 +1,228 @@
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::intcode::IntCodeComputer;
+
+/// The amount of candidates a worker claims at once.
+const CHUNK: usize = 64;
+
+/// Runs a program for every combination of values patched into a set of memory addresses, spread over
+/// threads, e.g. the noun and verb of day 2.
+///
+/// Candidates are numbered with the last parameter changing fastest, [`Sweep::first`] returns the lowest
+/// match in that order no matter how the work is scheduled. Candidates that fail with an error are
+/// skipped, set a budget on the computer beforehand to skip candidates that run too long.
+pub struct Sweep<'a> {
+    computer: &'a IntCodeComputer,
+    /// The address, values and amount of values of every parameter.
+    parameters: Vec<(usize, RangeInclusive<i64>, usize)>,
+    threads: usize,
+}
+
+impl<'a> Sweep<'a> {
+    /// Creates a sweep over copies of `computer`, add parameters with [`Sweep::parameter`].
+    pub fn new(computer: &'a IntCodeComputer) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        Self {
+            computer,
+            parameters: vec![],
+            threads,
+        }
+    }
+
+    /// Patches every value of `values` into `address`.
+    pub fn parameter(mut self, address: usize, values: RangeInclusive<i64>) -> Self {
+        // Counted once here, a range of every i64 holds one more value than fits a usize
+        let count = (i128::from(*values.end()) - i128::from(*values.start()) + 1).max(0);
+        let count = usize::try_from(count).unwrap_or(usize::MAX);
+        self.parameters.push((address, values, count));
+        self
+    }
+
+    /// The amount of worker threads, defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// The amount of candidates in the parameter space, saturating at `usize::MAX`.
+    pub fn len(&self) -> usize {
+        self.parameters
+            .iter()
+            .fold(1, |len, &(_, _, count)| len.saturating_mul(count))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The first parameter values for which the halted computer satisfies `predicate`.
+    pub fn first(&self, predicate: impl Fn(&IntCodeComputer) -> bool + Sync) -> Option<Vec<i64>> {
+        let found = AtomicUsize::new(usize::MAX);
+        let stop = |index| {
+            found.fetch_min(index, Ordering::Relaxed);
+            false
+        };
+        self.search(&predicate, stop, &found);
+
+        let index = found.into_inner();
+        (index != usize::MAX).then(|| self.values(index))
+    }
+
+    /// Every parameter tuple for which the halted computer satisfies `predicate`, in sweep order.
+    pub fn all(&self, predicate: impl Fn(&IntCodeComputer) -> bool + Sync) -> Vec<Vec<i64>> {
+        let matches = Mutex::new(vec![]);
+        let collect = |index| {
+            matches.lock().unwrap().push(index);
+            true
+        };
+        self.search(&predicate, collect, &AtomicUsize::new(usize::MAX));
+
+        let mut matches = matches.into_inner().unwrap();
+        matches.sort_unstable();
+        matches
+            .into_iter()
+            .map(|index| self.values(index))
+            .collect()
+    }
+
+    /// Evaluates every candidate below `limit`, calling `found` for every match.
+    /// A worker stops at its first match when `found` returns `false`.
+    fn search(
+        &self,
+        predicate: &(impl Fn(&IntCodeComputer) -> bool + Sync),
+        found: impl Fn(usize) -> bool + Sync,
+        limit: &AtomicUsize,
+    ) {
+        let total = self.len();
+        let next = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(total.div_ceil(CHUNK)) {
+                scope.spawn(|| {
+                    let mut computer = self.computer.clone();
+                    let start = computer.snapshot();
+
+                    'work: loop {
+                        let chunk = next.fetch_add(CHUNK, Ordering::Relaxed);
+                        if chunk >= total {
+                            break;
+                        }
+                        for index in chunk..(chunk + CHUNK).min(total) {
+                            if index > limit.load(Ordering::Relaxed) {
+                                break 'work;
+                            }
+
+                            computer.restore(&start);
+                            for ((address, _, _), value) in
+                                self.parameters.iter().zip(self.values(index))
+                            {
+                                computer.set(*address, value);
+                            }
+                            if computer.run().is_ok() && predicate(&computer) && !found(index) {
+                                break 'work;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// The parameter values of candidate `index`.
+    fn values(&self, mut index: usize) -> Vec<i64> {
+        let mut values = vec![0; self.parameters.len()];
+        for ((_, range, count), value) in self.parameters.iter().zip(&mut values).rev() {
+            *value = range.start().wrapping_add((index % count) as i64);
+            index /= count;
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adds the values at the addresses stored in 1 and 2.
+    const PROGRAM: &str = "1,0,0,0,99";
+
+    #[test]
+    fn test_first_and_all() {
+        let computer = IntCodeComputer::new(PROGRAM);
+        let sweep = Sweep::new(&computer)
+            .parameter(1, 0..=4)
+            .parameter(2, 0..=4)
+            .threads(3);
+        assert_eq!(sweep.len(), 25);
+        assert_eq!(
+            sweep.first(|computer| computer.get(0) == 100),
+            Some(vec![0, 4])
+        );
+        assert_eq!(
+            sweep.all(|computer| computer.get(0) == 100),
+            vec![vec![0, 4], vec![1, 4], vec![4, 0]]
+        );
+        assert_eq!(sweep.first(|computer| computer.get(0) == -1), None);
+
+        // The default amount of threads finds the same match
+        let found = Sweep::new(&computer)
+            .parameter(1, 0..=4)
+            .parameter(2, 0..=4)
+            .first(|computer| computer.get(0) == 100);
+        assert_eq!(found, Some(vec![0, 4]));
+    }
+
+    #[test]
+    fn test_matches_sequential_search() {
+        // A large space so every worker gets several chunks
+        let computer = IntCodeComputer::new(PROGRAM);
+        let sweep = Sweep::new(&computer)
+            .parameter(1, 0..=4)
+            .parameter(2, 0..=4)
+            .parameter(3, -20..=20)
+            .threads(4);
+        let expected = (0..sweep.len())
+            .map(|index| sweep.values(index))
+            .filter(|values| {
+                let mut computer = computer.clone();
+                values
+                    .iter()
+                    .zip(1..)
+                    .for_each(|(&value, address)| computer.set(address, value));
+                computer.run().is_ok() && computer.get(0) == 2
+            })
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(sweep.all(|computer| computer.get(0) == 2), expected);
+        assert_eq!(
+            sweep.first(|computer| computer.get(0) == 2),
+            expected.first().cloned()
+        );
+    }
+
+    #[test]
+    fn test_failures_are_skipped() {
+        // Jumps back to the start forever unless address 5 is zero
+        let mut computer = IntCodeComputer::new("1005,5,0,99,0,0");
+        computer.set_budget(Some(100));
+        let sweep = Sweep::new(&computer).parameter(5, -2..=2);
+        assert_eq!(sweep.all(|_| true), vec![vec![0]]);
+    }
+
+    #[test]
+    fn test_huge_space() {
+        let computer = IntCodeComputer::new(PROGRAM);
+        let sweep = Sweep::new(&computer)
+            .parameter(1, i64::MIN..=i64::MAX)
+            .parameter(2, 0..=4);
+        assert_eq!(sweep.len(), usize::MAX);
+        assert_eq!(sweep.values(0), vec![i64::MIN, 0]);
+        assert_eq!(sweep.values(6), vec![i64::MIN + 1, 1]);
+        assert!(Sweep::new(&computer)
+            .parameter(1, RangeInclusive::new(1, 0))
+            .is_empty());
+    }
+}
@@ -0,0 +1,683 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use crate::intcode::instruction::{Instruction, Mode, Opcode};
+use crate::intcode::{IntCodeComputer, IntcodeError};
+
+/// Symbolic runs follow the program concretely, so this many instructions mean it does not halt.
+const BUDGET: u64 = 1_000_000;
+
+/// The value of a memory cell or output in terms of the symbols, see [`SymbolicComputer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Constant(i64),
+    Symbol(String),
+    Add(Rc<Expr>, Rc<Expr>),
+    Multiply(Rc<Expr>, Rc<Expr>),
+    /// 1 if the left side is less than the right side, 0 otherwise.
+    LessThan(Rc<Expr>, Rc<Expr>),
+    /// 1 if both sides are equal, 0 otherwise.
+    Equals(Rc<Expr>, Rc<Expr>),
+    /// The memory cell at a symbolic address, as read at that moment.
+    Load(Rc<Expr>),
+}
+
+/// An expression of the form `constant + coefficient * symbol + ...`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Linear {
+    /// The coefficient of every symbol, symbols that cancel out are left out.
+    pub coefficients: BTreeMap<String, i64>,
+    pub constant: i64,
+}
+
+impl Expr {
+    pub fn symbol(name: &str) -> Self {
+        Expr::Symbol(name.to_string())
+    }
+
+    /// The value of the expression if it does not depend on any symbol.
+    pub fn constant(&self) -> Option<i64> {
+        match self {
+            Expr::Constant(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The expression as a linear combination of its symbols, `None` if symbols are multiplied together,
+    /// compared or used as an address, or if a coefficient overflows.
+    pub fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Constant(value) => Some(Linear {
+                constant: *value,
+                ..Linear::default()
+            }),
+            Expr::Symbol(name) => Some(Linear {
+                coefficients: BTreeMap::from([(name.clone(), 1)]),
+                constant: 0,
+            }),
+            Expr::Add(a, b) => {
+                let (mut a, b) = (a.linear()?, b.linear()?);
+                for (name, coefficient) in b.coefficients {
+                    let sum = a.coefficients.entry(name).or_default();
+                    *sum = sum.checked_add(coefficient)?;
+                }
+                a.coefficients.retain(|_, coefficient| *coefficient != 0);
+                a.constant = a.constant.checked_add(b.constant)?;
+                Some(a)
+            }
+            Expr::Multiply(a, b) => {
+                let (a, b) = (a.linear()?, b.linear()?);
+                let (factor, mut linear) =
+                    match (a.coefficients.is_empty(), b.coefficients.is_empty()) {
+                        (true, _) => (a.constant, b),
+                        (_, true) => (b.constant, a),
+                        _ => return None,
+                    };
+                for coefficient in linear.coefficients.values_mut() {
+                    *coefficient = coefficient.checked_mul(factor)?;
+                }
+                linear
+                    .coefficients
+                    .retain(|_, coefficient| *coefficient != 0);
+                linear.constant = linear.constant.checked_mul(factor)?;
+                Some(linear)
+            }
+            Expr::LessThan(..) | Expr::Equals(..) | Expr::Load(_) => None,
+        }
+    }
+
+    /// Solves `self == target` for the symbols in `bounds`, returning their values in the same order.
+    /// Of all solutions within the bounds the one with the lowest first value (then second, ...) is returned.
+    /// Returns `None` if the expression is not linear, uses a symbol missing from `bounds` or has no solution.
+    pub fn solve(&self, target: i64, bounds: &[(&str, RangeInclusive<i64>)]) -> Option<Vec<i64>> {
+        let linear = self.linear()?;
+        if linear
+            .coefficients
+            .keys()
+            .any(|name| bounds.iter().all(|(symbol, _)| symbol != name))
+        {
+            return None;
+        }
+
+        let terms = bounds
+            .iter()
+            .map(|(symbol, range)| {
+                let coefficient = linear.coefficients.get(*symbol).copied().unwrap_or(0);
+                (
+                    coefficient as i128,
+                    *range.start() as i128,
+                    *range.end() as i128,
+                )
+            })
+            .collect::<Vec<_>>();
+        let values = solve_linear(&terms, target as i128 - linear.constant as i128)?;
+        Some(values.into_iter().map(|value| value as i64).collect())
+    }
+
+    /// The sum, `None` if both are constants whose sum overflows.
+    fn add(a: &Expr, b: &Expr) -> Option<Expr> {
+        Some(match (a.constant(), b.constant()) {
+            (Some(a), Some(b)) => Expr::Constant(a.checked_add(b)?),
+            (Some(0), _) => b.clone(),
+            (_, Some(0)) => a.clone(),
+            _ => Expr::Add(Rc::new(a.clone()), Rc::new(b.clone())),
+        })
+    }
+
+    /// The product, `None` if both are constants whose product overflows.
+    fn multiply(a: &Expr, b: &Expr) -> Option<Expr> {
+        Some(match (a.constant(), b.constant()) {
+            (Some(a), Some(b)) => Expr::Constant(a.checked_mul(b)?),
+            (Some(0), _) | (_, Some(0)) => Expr::Constant(0),
+            (Some(1), _) => b.clone(),
+            (_, Some(1)) => a.clone(),
+            _ => Expr::Multiply(Rc::new(a.clone()), Rc::new(b.clone())),
+        })
+    }
+
+    fn less_than(a: &Expr, b: &Expr) -> Expr {
+        match (a.constant(), b.constant()) {
+            (Some(a), Some(b)) => Expr::Constant((a < b) as i64),
+            _ if a == b => Expr::Constant(0),
+            _ => Expr::LessThan(Rc::new(a.clone()), Rc::new(b.clone())),
+        }
+    }
+
+    fn equals(a: &Expr, b: &Expr) -> Expr {
+        match (a.constant(), b.constant()) {
+            (Some(a), Some(b)) => Expr::Constant((a == b) as i64),
+            _ if a == b => Expr::Constant(1),
+            _ => Expr::Equals(Rc::new(a.clone()), Rc::new(b.clone())),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Constant(value) => write!(f, "{value}"),
+            Expr::Symbol(name) => write!(f, "{name}"),
+            Expr::Add(a, b) => write!(f, "({a} + {b})"),
+            Expr::Multiply(a, b) => write!(f, "({a} * {b})"),
+            Expr::LessThan(a, b) => write!(f, "({a} < {b})"),
+            Expr::Equals(a, b) => write!(f, "({a} == {b})"),
+            Expr::Load(address) => write!(f, "[{address}]"),
+        }
+    }
+}
+
+/// Finds the lexicographically lowest values within the bounds for which `sum(coefficient * value) == rest`.
+/// The last two terms are solved with the extended Euclidean algorithm, earlier terms are enumerated.
+fn solve_linear(terms: &[(i128, i128, i128)], rest: i128) -> Option<Vec<i128>> {
+    match terms {
+        [] => (rest == 0).then(Vec::new),
+        [(_, low, high)] if low > high => None,
+        [(0, low, _)] => (rest == 0).then(|| vec![*low]),
+        [(coefficient, low, high)] => {
+            let value = rest.checked_div(*coefficient)?;
+            (rest.checked_rem(*coefficient)? == 0 && (low..=high).contains(&&value))
+                .then(|| vec![value])
+        }
+        [(a, x_low, x_high), (b, y_low, y_high)] if *a != 0 && *b != 0 => {
+            let (gcd, x, y) = extended_gcd(*a, *b);
+            if rest % gcd != 0 {
+                return None;
+            }
+            // Every solution is (x0 + k * x_step, y0 + k * y_step)
+            let (x0, y0) = (x.checked_mul(rest / gcd)?, y.checked_mul(rest / gcd)?);
+            let (x_step, y_step) = (b / gcd, -a / gcd);
+            let (x_min, x_max) = steps(*x_low, *x_high, x0, x_step)?;
+            let (y_min, y_max) = steps(*y_low, *y_high, y0, y_step)?;
+            let (min, max) = (x_min.max(y_min), x_max.min(y_max));
+            if min > max {
+                return None;
+            }
+            let k = if x_step > 0 { min } else { max };
+            Some(vec![
+                x0.checked_add(k.checked_mul(x_step)?)?,
+                y0.checked_add(k.checked_mul(y_step)?)?,
+            ])
+        }
+        [(coefficient, low, high), rest_terms @ ..] => (*low..=*high).find_map(|value| {
+            let mut values = solve_linear(
+                rest_terms,
+                rest.checked_sub(coefficient.checked_mul(value)?)?,
+            )?;
+            values.insert(0, value);
+            Some(values)
+        }),
+    }
+}
+
+/// Returns `(gcd, x, y)` with `a * x + b * y == gcd` and a positive `gcd`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a.abs(), a.signum(), 0);
+    }
+    let (gcd, x, y) = extended_gcd(b, a % b);
+    (gcd, y, x - (a / b) * y)
+}
+
+/// The range of `k` for which `start + k * step` lies within `low..=high`, `step` is not 0. `None` if
+/// the bounds overflow.
+fn steps(low: i128, high: i128, start: i128, step: i128) -> Option<(i128, i128)> {
+    let (low, high) = if step > 0 {
+        (low.checked_sub(start)?, high.checked_sub(start)?)
+    } else {
+        (high.checked_sub(start)?, low.checked_sub(start)?)
+    };
+    Some((
+        floor_div(low.checked_neg()?, step)?.checked_neg()?,
+        floor_div(high, step)?,
+    ))
+}
+
+fn floor_div(a: i128, b: i128) -> Option<i128> {
+    let quotient = a.checked_div(b)?;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        quotient.checked_sub(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Why a symbolic run stopped before the program halted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolicError {
+    /// The program fails the same way a concrete run would.
+    Intcode(IntcodeError),
+    /// An instruction, jump, written address or relative base depends on a symbol, so the program can
+    /// not be followed without choosing values.
+    DependsOnSymbol { instruction_pointer: usize },
+}
+
+impl From<IntcodeError> for SymbolicError {
+    fn from(error: IntcodeError) -> Self {
+        SymbolicError::Intcode(error)
+    }
+}
+
+impl Error for SymbolicError {}
+
+impl Display for SymbolicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolicError::Intcode(error) => write!(f, "{error}"),
+            SymbolicError::DependsOnSymbol {
+                instruction_pointer,
+            } => {
+                write!(
+                    f,
+                    "Intcode: control flow depends on a symbol at {instruction_pointer:04}"
+                )
+            }
+        }
+    }
+}
+
+/// Runs an Intcode program with some memory cells or inputs replaced by symbols and records what the program
+/// computes from them, e.g. `((noun * 250000) + verb) + 337061` in address 0 of day 2.
+///
+/// Control flow has to be concrete: the program stops with [`SymbolicError::DependsOnSymbol`] once an
+/// instruction, a jump condition or target, a written address or the relative base depends on a symbol.
+/// Reading at a symbolic address is allowed and results in an [`Expr::Load`].
+#[derive(Clone, Debug)]
+pub struct SymbolicComputer {
+    /// Every cell that is not the constant 0, so sparse memory stays sparse.
+    memory: HashMap<usize, Expr>,
+    /// One past the highest address that was loaded or written.
+    length: usize,
+    instruction_pointer: usize,
+    relative_base: i64,
+    input: VecDeque<Expr>,
+    output: Vec<Expr>,
+}
+
+impl SymbolicComputer {
+    /// Starts from the current state of `computer`, including its queued input.
+    pub fn new(computer: &IntCodeComputer) -> Self {
+        Self {
+            memory: computer
+                .memory()
+                .cells()
+                .into_iter()
+                .map(|(address, value)| (address, Expr::Constant(value)))
+                .collect(),
+            length: computer.memory().len(),
+            instruction_pointer: computer.instruction_pointer(),
+            relative_base: computer.relative_base(),
+            input: computer
+                .pending_input()
+                .map(|&value| Expr::Constant(value))
+                .collect(),
+            output: vec![],
+        }
+    }
+
+    /// Replaces the memory cell at `address` with the symbol `name`.
+    pub fn symbol(&mut self, address: usize, name: &str) {
+        self.set(address, Expr::symbol(name));
+    }
+
+    /// Queues an input value, e.g. [`Expr::symbol`].
+    pub fn push_input(&mut self, value: Expr) {
+        self.input.push_back(value);
+    }
+
+    /// The expression stored at `address`, memory beyond the program reads as 0.
+    pub fn get(&self, address: usize) -> Expr {
+        self.memory
+            .get(&address)
+            .cloned()
+            .unwrap_or(Expr::Constant(0))
+    }
+
+    /// The addresses and expressions of every cell that is not the constant 0, by address.
+    pub fn cells(&self) -> Vec<(usize, Expr)> {
+        let mut cells = self
+            .memory
+            .iter()
+            .map(|(&address, expr)| (address, expr.clone()))
+            .collect::<Vec<_>>();
+        cells.sort_unstable_by_key(|&(address, _)| address);
+        cells
+    }
+
+    /// One past the highest address that was loaded or written.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Every value output so far.
+    pub fn output(&self) -> &[Expr] {
+        &self.output
+    }
+
+    /// Runs the program until it halts.
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        for _ in 0..BUDGET {
+            if self.step()? {
+                return Ok(());
+            }
+        }
+        let instruction_pointer = self.instruction_pointer;
+        let instruction = self.get(instruction_pointer).constant().unwrap_or_default();
+        Err(IntcodeError::BudgetExhausted {
+            instruction_pointer,
+            instruction,
+        }
+        .into())
+    }
+
+    /// Executes a single instruction, returns `true` once the program halted.
+    fn step(&mut self) -> Result<bool, SymbolicError> {
+        let instruction_pointer = self.instruction_pointer;
+        let value = self.concrete(&self.get(instruction_pointer))?;
+        let instruction =
+            Instruction::decode(value).ok_or_else(|| match Opcode::from_instruction(value) {
+                Some(_) => IntcodeError::InvalidMode {
+                    instruction_pointer,
+                    instruction: value,
+                },
+                None => IntcodeError::UnknownOpcode {
+                    instruction_pointer,
+                    instruction: value,
+                },
+            })?;
+
+        let mut next = instruction_pointer + instruction.length();
+        match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let (a, b) = (
+                    self.parameter(&instruction, 0)?,
+                    self.parameter(&instruction, 1)?,
+                );
+                let result = match instruction.opcode {
+                    Opcode::Add => Expr::add(&a, &b),
+                    Opcode::Multiply => Expr::multiply(&a, &b),
+                    Opcode::LessThan => Some(Expr::less_than(&a, &b)),
+                    _ => Some(Expr::equals(&a, &b)),
+                };
+                let overflow = IntcodeError::Overflow {
+                    instruction_pointer,
+                    instruction: value,
+                };
+                self.write(&instruction, 2, result.ok_or(overflow)?)?;
+            }
+            Opcode::Input => {
+                // Validate the target before consuming input
+                self.write_address(&instruction, 0)?;
+                let value = self.input.pop_front().ok_or(IntcodeError::InputExhausted {
+                    instruction_pointer,
+                    instruction: value,
+                })?;
+                self.write(&instruction, 0, value)?;
+            }
+            Opcode::Output => {
+                let value = self.parameter(&instruction, 0)?;
+                self.output.push(value);
+            }
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let condition = self.concrete(&self.parameter(&instruction, 0)?)? != 0;
+                if condition == (instruction.opcode == Opcode::JumpIfTrue) {
+                    let target = self.concrete(&self.parameter(&instruction, 1)?)?;
+                    if target < 0 {
+                        return Err(IntcodeError::NegativeJump {
+                            instruction_pointer,
+                            instruction: value,
+                            target,
+                        }
+                        .into());
+                    }
+                    next = target as usize;
+                }
+            }
+            Opcode::AdjustRelativeBase => {
+                let offset = self.concrete(&self.parameter(&instruction, 0)?)?;
+                let overflow = IntcodeError::Overflow {
+                    instruction_pointer,
+                    instruction: value,
+                };
+                self.relative_base = self.relative_base.checked_add(offset).ok_or(overflow)?;
+            }
+            Opcode::Halt => return Ok(true),
+        }
+        self.instruction_pointer = next;
+        Ok(false)
+    }
+
+    fn set(&mut self, address: usize, value: Expr) {
+        self.length = self.length.max(address + 1);
+        if value == Expr::Constant(0) {
+            self.memory.remove(&address);
+        } else {
+            self.memory.insert(address, value);
+        }
+    }
+
+    /// The value of `expr`, or an error if it depends on a symbol.
+    fn concrete(&self, expr: &Expr) -> Result<i64, SymbolicError> {
+        expr.constant().ok_or(SymbolicError::DependsOnSymbol {
+            instruction_pointer: self.instruction_pointer,
+        })
+    }
+
+    /// The address given by the parameter at `index`, which may depend on a symbol.
+    fn address(&self, instruction: &Instruction, index: usize) -> Result<Expr, SymbolicError> {
+        let instruction_pointer = self.instruction_pointer;
+        let raw = self.get(instruction_pointer + 1 + index);
+        let address = match instruction.modes[index] {
+            Mode::Position => raw,
+            Mode::Relative => Expr::add(&Expr::Constant(self.relative_base), &raw).ok_or(
+                IntcodeError::Overflow {
+                    instruction_pointer,
+                    instruction: instruction.encode(),
+                },
+            )?,
+            Mode::Immediate => {
+                return Err(IntcodeError::ImmediateWrite {
+                    instruction_pointer,
+                    instruction: instruction.encode(),
+                }
+                .into())
+            }
+        };
+        match address.constant() {
+            Some(address) if address < 0 => Err(IntcodeError::NegativeAddress {
+                instruction_pointer,
+                instruction: instruction.encode(),
+                address,
+            }
+            .into()),
+            _ => Ok(address),
+        }
+    }
+
+    /// Resolves the parameter at `index` according to its mode.
+    fn parameter(&self, instruction: &Instruction, index: usize) -> Result<Expr, SymbolicError> {
+        if instruction.modes[index] == Mode::Immediate {
+            return Ok(self.get(self.instruction_pointer + 1 + index));
+        }
+        let address = self.address(instruction, index)?;
+        Ok(match address.constant() {
+            Some(address) => self.get(address as usize),
+            None => Expr::Load(Rc::new(address)),
+        })
+    }
+
+    /// The concrete address the parameter at `index` writes to.
+    fn write_address(
+        &self,
+        instruction: &Instruction,
+        index: usize,
+    ) -> Result<usize, SymbolicError> {
+        let address = self.address(instruction, index)?;
+        Ok(self.concrete(&address)? as usize)
+    }
+
+    fn write(
+        &mut self,
+        instruction: &Instruction,
+        index: usize,
+        value: Expr,
+    ) -> Result<(), SymbolicError> {
+        let address = self.write_address(instruction, index)?;
+        self.set(address, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Like day 2: `[noun] + [verb]` is stored and overwritten, then `noun * 250000 + verb + 16690718`.
+    const PROGRAM: &str = "1,0,0,3,2,1,19,3,1,3,2,3,1,3,20,0,99,0,0,250000,16690718";
+
+    fn noun_verb() -> SymbolicComputer {
+        let mut computer = SymbolicComputer::new(&IntCodeComputer::new(PROGRAM));
+        computer.symbol(1, "noun");
+        computer.symbol(2, "verb");
+        computer.run().unwrap();
+        computer
+    }
+
+    #[test]
+    fn test_expression() {
+        let computer = noun_verb();
+        assert_eq!(
+            computer.get(0).to_string(),
+            "(((noun * 250000) + verb) + 16690718)"
+        );
+        let linear = computer.get(0).linear().unwrap();
+        assert_eq!(
+            linear.coefficients,
+            BTreeMap::from([("noun".to_string(), 250000), ("verb".to_string(), 1)])
+        );
+        assert_eq!(linear.constant, 16690718);
+    }
+
+    #[test]
+    fn test_solve() {
+        let expr = noun_verb().get(0);
+        let bounds = [("noun", 0..=99), ("verb", 0..=99)];
+        assert_eq!(expr.solve(19690720, &bounds), Some(vec![12, 2]));
+        assert_eq!(expr.solve(19690720 + 100, &bounds), None);
+
+        // The answer agrees with a concrete run
+        let mut computer = IntCodeComputer::new(PROGRAM);
+        computer.set(1, 12);
+        computer.set(2, 2);
+        computer.run().unwrap();
+        assert_eq!(computer.get(0), 19690720);
+    }
+
+    #[test]
+    fn test_solve_lowest() {
+        // 6x + 4y = 22 has the solutions (1, 4) and (3, 1)
+        let expr = Expr::add(
+            &Expr::multiply(&Expr::symbol("x"), &Expr::Constant(6)).unwrap(),
+            &Expr::multiply(&Expr::Constant(4), &Expr::symbol("y")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            expr.solve(22, &[("x", 0..=10), ("y", 0..=10)]),
+            Some(vec![1, 4])
+        );
+        assert_eq!(
+            expr.solve(22, &[("x", 2..=10), ("y", 0..=10)]),
+            Some(vec![3, 1])
+        );
+        assert_eq!(expr.solve(23, &[("x", 0..=10), ("y", 0..=10)]), None);
+        assert_eq!(
+            expr.solve(22, &[("z", 0..=3), ("x", 0..=10), ("y", 0..=10)]),
+            Some(vec![0, 1, 4])
+        );
+        assert_eq!(expr.solve(22, &[("x", 0..=10)]), None);
+
+        // x - y = 3
+        let expr = Expr::add(
+            &Expr::symbol("x"),
+            &Expr::multiply(&Expr::symbol("y"), &Expr::Constant(-1)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            expr.solve(3, &[("x", 0..=10), ("y", 0..=10)]),
+            Some(vec![3, 0])
+        );
+        assert_eq!(
+            expr.solve(3, &[("x", -10..=10), ("y", -10..=10)]),
+            Some(vec![-7, -10])
+        );
+    }
+
+    #[test]
+    fn test_solve_overflow() {
+        // x * MAX + x overflows the coefficient of x
+        let x_max = Expr::multiply(&Expr::symbol("x"), &Expr::Constant(i64::MAX)).unwrap();
+        let expr = Expr::add(&x_max, &Expr::symbol("x")).unwrap();
+        assert_eq!(expr.linear(), None);
+        assert_eq!(expr.solve(0, &[("x", 0..=10)]), None);
+
+        // The extreme coefficients and bounds of MAX * x + MIN * y = MIN stay within i128
+        let y_min = Expr::multiply(&Expr::symbol("y"), &Expr::Constant(i64::MIN)).unwrap();
+        let expr = Expr::add(&x_max, &y_min).unwrap();
+        assert_eq!(
+            expr.solve(
+                i64::MIN,
+                &[("x", i64::MIN..=i64::MAX), ("y", i64::MIN..=i64::MAX)]
+            ),
+            Some(vec![i64::MIN, i64::MIN + 2])
+        );
+    }
+
+    #[test]
+    fn test_symbolic_input() {
+        // Outputs the sum of two inputs
+        let mut computer =
+            SymbolicComputer::new(&IntCodeComputer::new("3,11,3,12,1,11,12,13,4,13,99,0,0,0"));
+        computer.push_input(Expr::symbol("a"));
+        computer.push_input(Expr::Constant(5));
+        computer.run().unwrap();
+        assert_eq!(
+            computer
+                .output()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["(a + 5)"]
+        );
+    }
+
+    #[test]
+    fn test_symbolic_control_flow() {
+        // Jumps depending on the input
+        let mut computer = SymbolicComputer::new(&IntCodeComputer::new("3,7,1005,7,6,99,99,0"));
+        computer.push_input(Expr::symbol("a"));
+        assert_eq!(
+            computer.run(),
+            Err(SymbolicError::DependsOnSymbol {
+                instruction_pointer: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_sparse_memory() {
+        // Adds the input to a cell a trillion cells past the program, which only exists in sparse memory
+        let mut program = IntCodeComputer::new("109,1000000000000,203,0,22201,0,7,7,99");
+        program.set(1_000_000_000_007, 2);
+        let mut computer = SymbolicComputer::new(&program);
+        computer.push_input(Expr::symbol("a"));
+        computer.run().unwrap();
+        assert_eq!(computer.len(), 1_000_000_000_008);
+        assert_eq!(computer.get(1_000_000_000_007).to_string(), "(a + 2)");
+    }
+}
@@ -0,0 +1,201 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use iter_tools::Itertools;
+
+use crate::intcode::{IntCodeComputer, IntcodeError, RunState};
+
+/// How the output of the last computer of a [`Topology`] is wired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wiring {
+    /// The output of the last computer is the result, e.g. the amplifiers of day 7 part one.
+    Pipeline,
+    /// The output of the last computer feeds back into the first, e.g. the feedback loop of day 7 part two.
+    Ring,
+}
+
+/// Why a [`Topology`] stopped before every computer halted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopologyError {
+    Intcode(IntcodeError),
+    /// No computer produced output anymore while `computer` (its index in the chain) still waits for input.
+    Starved {
+        computer: usize,
+        instruction_pointer: usize,
+    },
+}
+
+impl Error for TopologyError {}
+
+impl Display for TopologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopologyError::Intcode(error) => write!(f, "{error}"),
+            TopologyError::Starved {
+                computer,
+                instruction_pointer,
+            } => {
+                write!(f, "Topology: computer {computer} waits for input at {instruction_pointer:04} that never comes")
+            }
+        }
+    }
+}
+
+impl From<IntcodeError> for TopologyError {
+    fn from(error: IntcodeError) -> Self {
+        TopologyError::Intcode(error)
+    }
+}
+
+/// Intcode computers chained output to input (day 7).
+pub struct Topology {
+    computers: Vec<IntCodeComputer>,
+    wiring: Wiring,
+}
+
+impl Topology {
+    /// Creates an empty topology, add computers with [`Topology::computer`].
+    pub fn new(wiring: Wiring) -> Self {
+        Self {
+            computers: vec![],
+            wiring,
+        }
+    }
+
+    /// Creates a topology with a copy of `program` for every seed, e.g. the phase settings of day 7.
+    pub fn seeded(program: &IntCodeComputer, wiring: Wiring, seeds: &[i64]) -> Self {
+        seeds.iter().fold(Self::new(wiring), |topology, &seed| {
+            topology.computer(program.clone(), &[seed])
+        })
+    }
+
+    /// Appends a computer to the chain, the `seed` values are queued before any signal.
+    pub fn computer(mut self, mut computer: IntCodeComputer, seed: &[i64]) -> Self {
+        seed.iter().for_each(|&value| computer.push_input(value));
+        self.computers.push(computer);
+        self
+    }
+
+    /// Feeds `signal` into the first computer and runs until every computer halted.
+    /// Returns the last output of the last computer, `None` if it never produced any, and fails with
+    /// [`TopologyError::Starved`] once nothing changes anymore while a computer still waits for input.
+    pub fn run(mut self, signal: i64) -> Result<Option<i64>, TopologyError> {
+        let count = self.computers.len();
+        let Some(first) = self.computers.first_mut() else {
+            return Ok(None);
+        };
+        first.push_input(signal);
+
+        let mut result = None;
+        let mut halted = vec![false; count];
+        loop {
+            let mut progress = false;
+            for (index, done) in halted.iter_mut().enumerate() {
+                // Run until the computer waits for input or halts
+                let mut outputs = vec![];
+                loop {
+                    match self.computers[index].resume()? {
+                        RunState::Output(value) => outputs.push(value),
+                        RunState::NeedsInput => break,
+                        RunState::Halted => {
+                            *done = true;
+                            break;
+                        }
+                    }
+                }
+                progress |= !outputs.is_empty();
+
+                let is_last = index == count - 1;
+                if is_last {
+                    result = outputs.last().copied().or(result);
+                }
+
+                let target = match (is_last, self.wiring) {
+                    (false, _) => Some(index + 1),
+                    (true, Wiring::Ring) => Some(0),
+                    (true, Wiring::Pipeline) => None,
+                };
+                if let Some(target) = target {
+                    outputs
+                        .into_iter()
+                        .for_each(|value| self.computers[target].push_input(value));
+                }
+            }
+
+            // Without any new output nothing can change anymore, every computer halted or is starved
+            if !progress {
+                break;
+            }
+        }
+
+        match halted.iter().position(|&halted| !halted) {
+            Some(computer) => Err(TopologyError::Starved {
+                computer,
+                instruction_pointer: self.computers[computer].instruction_pointer(),
+            }),
+            None => Ok(result),
+        }
+    }
+}
+
+/// Evaluates every permutation of `phases` and returns the best phase setting together with its signal.
+/// Settings for which the program fails are skipped.
+pub fn best_phase_setting(
+    program: &IntCodeComputer,
+    phases: &[i64],
+    wiring: Wiring,
+) -> Option<(Vec<i64>, i64)> {
+    phases
+        .iter()
+        .copied()
+        .permutations(phases.len())
+        .filter_map(|setting| {
+            let signal = Topology::seeded(program, wiring, &setting).run(0).ok()??;
+            Some((setting, signal))
+        })
+        .max_by_key(|(_, signal)| *signal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline() {
+        let program = IntCodeComputer::new("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+        let result = best_phase_setting(&program, &[0, 1, 2, 3, 4], Wiring::Pipeline);
+        assert_eq!(result, Some((vec![4, 3, 2, 1, 0], 43210)));
+
+        let signal = Topology::new(Wiring::Pipeline)
+            .computer(program.clone(), &[4])
+            .computer(program, &[3])
+            .run(0);
+        assert_eq!(signal, Ok(Some(43)));
+    }
+
+    #[test]
+    fn test_ring() {
+        let program = IntCodeComputer::new(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5",
+        );
+        let result = best_phase_setting(&program, &[5, 6, 7, 8, 9], Wiring::Ring);
+        assert_eq!(result, Some((vec![9, 8, 7, 6, 5], 139629729)));
+    }
+
+    #[test]
+    fn test_starved() {
+        // The second computer reads two inputs but only ever receives one
+        let adder = IntCodeComputer::new("3,11,3,12,1,11,12,13,4,13,99,0,0,0");
+        let result = Topology::new(Wiring::Pipeline)
+            .computer(adder.clone(), &[1])
+            .computer(adder, &[])
+            .run(2);
+        assert_eq!(
+            result,
+            Err(TopologyError::Starved {
+                computer: 1,
+                instruction_pointer: 2
+            })
+        );
+    }
+}
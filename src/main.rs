@@ -1,4 +1,6 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{
+    all, ascii, debug, disassemble, download, read, run, scaffold, solve, time,
+};
 use args::{parse, AppArguments};
 
 #[cfg(feature = "today")]
@@ -7,6 +9,7 @@ use advent_of_code::template::Day;
 use std::process;
 
 mod args {
+    use advent_of_code::template::commands::run;
     use advent_of_code::template::Day;
     use std::process;
 
@@ -27,6 +30,8 @@ mod args {
             release: bool,
             dhat: bool,
             submit: Option<u8>,
+            profile: bool,
+            profile_json: Option<String>,
         },
         All {
             release: bool,
@@ -36,6 +41,21 @@ mod args {
             day: Option<Day>,
             store: bool,
         },
+        Disassemble {
+            source: String,
+        },
+        Debug {
+            source: String,
+        },
+        Ascii {
+            source: String,
+            transcript: Option<String>,
+            save: Option<String>,
+        },
+        IntcodeRun {
+            source: String,
+            options: run::RunOptions,
+        },
         #[cfg(feature = "today")]
         Today,
     }
@@ -73,6 +93,41 @@ mod args {
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
+                profile: args.contains("--profile"),
+                profile_json: args.opt_value_from_str("--profile-json")?,
+            },
+            Some("disassemble") => AppArguments::Disassemble {
+                source: args.free_from_str()?,
+            },
+            Some("debug") => AppArguments::Debug {
+                source: args.free_from_str()?,
+            },
+            Some("ascii") => AppArguments::Ascii {
+                transcript: args.opt_value_from_str("--transcript")?,
+                save: args.opt_value_from_str("--save")?,
+                source: args.free_from_str()?,
+            },
+            Some("intcode") => match args.subcommand()?.as_deref() {
+                Some("run") => AppArguments::IntcodeRun {
+                    options: run::RunOptions {
+                        input: args.opt_value_from_str("--input")?,
+                        input_file: args.opt_value_from_str("--input-file")?,
+                        ascii: args.contains("--ascii"),
+                        patches: args.opt_value_from_str("--patch")?,
+                        budget: args.opt_value_from_str("--budget")?,
+                        trace: args.contains("--trace"),
+                        cells: args.opt_value_from_str("--cells")?,
+                    },
+                    source: args.free_from_str()?,
+                },
+                Some(x) => {
+                    eprintln!("Unknown intcode command: {x}");
+                    process::exit(1);
+                }
+                None => {
+                    eprintln!("No intcode command specified.");
+                    process::exit(1);
+                }
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -117,7 +172,17 @@ fn main() {
                 release,
                 dhat,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+                profile,
+                profile_json,
+            } => solve::handle(day, release, dhat, submit, profile, profile_json),
+            AppArguments::Disassemble { source } => disassemble::handle(&source),
+            AppArguments::Debug { source } => debug::handle(&source),
+            AppArguments::Ascii {
+                source,
+                transcript,
+                save,
+            } => ascii::handle(&source, transcript.as_deref(), save.as_deref()),
+            AppArguments::IntcodeRun { source, options } => run::handle(&source, options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
@@ -0,0 +1,80 @@
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, Write};
+use std::process;
+
+use crate::intcode::ascii::AsciiComputer;
+use crate::template::read_computer;
+
+/// Connects an ASCII Intcode program to the terminal, optionally recording everything to a transcript file
+/// and saving the session on exit so it can be continued later.
+pub fn handle(source: &str, transcript_path: Option<&str>, save_path: Option<&str>) {
+    let computer = match read_computer(source) {
+        Ok(computer) => computer,
+        Err(e) => {
+            eprintln!("Failed to read program \"{source}\": {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut transcript = match transcript_path.map(File::create).transpose() {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            eprintln!("Failed to create transcript file: {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut computer = AsciiComputer::new(computer);
+    let mut lines = stdin().lock().lines();
+    loop {
+        let output = match computer.run() {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        };
+
+        let mut text = output.text;
+        for number in output.numbers {
+            text.push_str(&format!("{number}\n"));
+        }
+        print!("{text}");
+        let _ = stdout().flush();
+        record(&mut transcript, &text);
+
+        if output.halted {
+            break;
+        }
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        record(&mut transcript, &format!("{line}\n"));
+        computer.send_line(&line);
+    }
+
+    if let Some(path) = transcript_path {
+        println!("🎄 Transcript written to \"{path}\".");
+    }
+
+    if let Some(path) = save_path {
+        match computer.computer().save(path) {
+            Ok(()) => {
+                println!("🎄 Session saved to \"{path}\", continue it with `cargo ascii {path}`.")
+            }
+            Err(e) => {
+                eprintln!("Failed to save session: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn record(transcript: &mut Option<File>, text: &str) {
+    if let Some(file) = transcript {
+        if let Err(e) = file.write_all(text.as_bytes()) {
+            eprintln!("Failed to write transcript: {e}");
+        }
+    }
+}
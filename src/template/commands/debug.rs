@@ -0,0 +1,39 @@
+use std::io::{stdin, stdout, BufRead, Write};
+use std::process;
+
+use crate::intcode::debugger::Debugger;
+use crate::template::read_computer;
+
+pub fn handle(source: &str) {
+    let computer = match read_computer(source) {
+        Ok(computer) => computer,
+        Err(e) => {
+            eprintln!("Failed to read program \"{source}\": {e}");
+            process::exit(1);
+        }
+    };
+
+    let mut debugger = Debugger::new(computer);
+    println!("🎄 Debugging \"{source}\", type `help` for a list of commands.");
+    if let Ok(listing) = debugger.execute("list") {
+        println!("{listing}");
+    }
+
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("(intcode) ");
+        let _ = stdout().flush();
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        if matches!(line.trim(), "quit" | "q" | "exit") {
+            break;
+        }
+
+        match debugger.execute(&line) {
+            Ok(output) => println!("{output}"),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
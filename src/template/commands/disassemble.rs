@@ -0,0 +1,18 @@
+use std::process;
+
+use crate::intcode::disassembler::disassemble;
+use crate::template::read_computer;
+
+pub fn handle(source: &str) {
+    let computer = match read_computer(source) {
+        Ok(computer) => computer,
+        Err(e) => {
+            eprintln!("Failed to read program \"{source}\": {e}");
+            process::exit(1);
+        }
+    };
+
+    for line in disassemble(computer.memory()) {
+        println!("{line}");
+    }
+}
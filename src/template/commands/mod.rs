@@ -1,6 +1,10 @@
 pub mod all;
+pub mod ascii;
+pub mod debug;
+pub mod disassemble;
 pub mod download;
 pub mod read;
+pub mod run;
 pub mod scaffold;
 pub mod solve;
 pub mod time;
@@ -0,0 +1,173 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::process;
+
+use crate::intcode::ascii::{decode, encode};
+use crate::intcode::disassembler::disassemble_at;
+use crate::intcode::{IntCodeComputer, RunState};
+use crate::template::read_computer;
+
+/// Everything `cargo intcode run` can be told besides the program.
+pub struct RunOptions {
+    /// Comma separated input values, or a line of text in ASCII mode.
+    pub input: Option<String>,
+    pub input_file: Option<String>,
+    pub ascii: bool,
+    /// Memory patches like `1=12,2=2`, applied before running.
+    pub patches: Option<String>,
+    pub budget: Option<u64>,
+    /// Prints every executed instruction to stderr.
+    pub trace: bool,
+    /// Comma separated addresses to print after running, by default address 0 and every patched address.
+    pub cells: Option<String>,
+}
+
+/// Runs an Intcode program on the given input and prints its output and the final memory cells.
+pub fn handle(source: &str, options: RunOptions) {
+    let mut computer = match read_computer(source) {
+        Ok(computer) => computer,
+        Err(e) => {
+            eprintln!("Failed to read program \"{source}\": {e}");
+            process::exit(1);
+        }
+    };
+
+    let patches = exit_on_error(options.patches.as_deref().map(parse_patches).transpose())
+        .unwrap_or_default();
+    for &(address, value) in &patches {
+        computer.set(address, value);
+    }
+    computer.set_budget(options.budget);
+
+    let mut input = options.input.map_or(String::new(), |input| {
+        if options.ascii {
+            format!("{input}\n")
+        } else {
+            format!("{input},")
+        }
+    });
+    if let Some(path) = &options.input_file {
+        match fs::read_to_string(path) {
+            Ok(text) => input.push_str(&text),
+            Err(e) => {
+                eprintln!("Failed to read input file \"{path}\": {e}");
+                process::exit(1);
+            }
+        }
+    }
+    let input = if options.ascii {
+        encode(&input)
+    } else {
+        exit_on_error(parse_values(&input))
+    };
+    input
+        .into_iter()
+        .for_each(|value| computer.push_input(value));
+
+    let result = run(&mut computer, options.trace);
+    print_output(computer.output(), options.ascii);
+
+    let cells = match options.cells.as_deref() {
+        Some(cells) => exit_on_error(parse_addresses(cells)),
+        None => std::iter::once(0)
+            .chain(patches.iter().map(|&(address, _)| address))
+            .collect(),
+    };
+    for address in cells {
+        println!("[{address}] = {}", computer.get(address));
+    }
+    println!("Executed {} instructions.", computer.executed());
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+}
+
+/// Runs until the program halts, failing when it waits for input that was not given.
+fn run(computer: &mut IntCodeComputer, trace: bool) -> Result<(), String> {
+    loop {
+        if trace {
+            let address = computer.instruction_pointer();
+            let line = disassemble_at(computer.memory(), address).map_or(
+                format!("{address:04}: DATA {}", computer.get(address)),
+                |line| line.to_string(),
+            );
+            eprintln!("{line:<48} rb = {}", computer.relative_base());
+        }
+        match computer.step().map_err(|e| e.to_string())? {
+            Some(RunState::Halted) => return Ok(()),
+            Some(RunState::NeedsInput) => {
+                return Err("The program waits for more input than was given.".into())
+            }
+            _ => {}
+        }
+    }
+}
+
+fn print_output(output: &[i64], ascii: bool) {
+    if !ascii {
+        let values = output.iter().map(i64::to_string).collect::<Vec<_>>();
+        println!("Output: {}", values.join(","));
+        return;
+    }
+
+    let (text, numbers) = decode(output);
+    print!("{text}");
+    if !text.is_empty() && !text.ends_with('\n') {
+        println!();
+    }
+    for number in numbers {
+        println!("{number}");
+    }
+}
+
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    })
+}
+
+/// Values separated by commas or whitespace, like a puzzle input.
+fn parse_values(text: &str) -> Result<Vec<i64>, String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|e| format!("Invalid input value \"{value}\": {e}"))
+        })
+        .collect()
+}
+
+fn parse_addresses(text: &str) -> Result<BTreeSet<usize>, String> {
+    text.split(',')
+        .map(|address| {
+            address
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid address \"{address}\": {e}"))
+        })
+        .collect()
+}
+
+/// Patches like `1=12,2=2`, setting address 1 to 12 and address 2 to 2.
+fn parse_patches(text: &str) -> Result<Vec<(usize, i64)>, String> {
+    text.split(',')
+        .map(|patch| {
+            let (address, value) = patch
+                .split_once('=')
+                .ok_or(format!("Expected a patch like \"1=12\", got \"{patch}\"."))?;
+            let address = address
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format!("Invalid address in patch \"{patch}\": {e}"))?;
+            let value = value
+                .trim()
+                .parse::<i64>()
+                .map_err(|e| format!("Invalid value in patch \"{patch}\": {e}"))?;
+            Ok((address, value))
+        })
+        .collect()
+}
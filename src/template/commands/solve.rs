@@ -1,8 +1,16 @@
 use std::process::{Command, Stdio};
 
+use crate::intcode::profile::{PROFILE_JSON_VAR, PROFILE_VAR};
 use crate::template::Day;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+pub fn handle(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    submit_part: Option<u8>,
+    profile: bool,
+    profile_json: Option<String>,
+) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
     if dhat {
@@ -23,12 +31,20 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
         cmd_args.push(submit_part.to_string());
     }
 
-    let mut cmd = Command::new("cargo")
-        .args(&cmd_args)
+    let mut cmd = Command::new("cargo");
+    cmd.args(&cmd_args)
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .spawn()
-        .unwrap();
+        .stderr(Stdio::inherit());
+
+    // The solution reads these at runtime, so profiling does not need a rebuild.
+    if profile {
+        cmd.env(PROFILE_VAR, "1");
+    }
+    if let Some(profile_json) = profile_json {
+        cmd.env(PROFILE_JSON_VAR, profile_json);
+    }
+
+    let mut cmd = cmd.spawn().unwrap();
 
     cmd.wait().unwrap();
 }
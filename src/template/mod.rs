@@ -1,5 +1,7 @@
 use std::{env, fs};
 
+use crate::intcode::IntCodeComputer;
+
 pub mod aoc_cli;
 pub mod commands;
 pub mod runner;
@@ -36,6 +38,28 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
     f.expect("could not open input file")
 }
 
+/// Helper function that reads an Intcode program, either the input of a day (e.g. `05`) or any file path.
+pub fn read_program(source: &str) -> Result<String, std::io::Error> {
+    match source.parse::<Day>() {
+        Ok(day) => fs::read_to_string(
+            env::current_dir()?
+                .join("data")
+                .join("inputs")
+                .join(format!("{day}.txt")),
+        ),
+        Err(_) => fs::read_to_string(source),
+    }
+}
+
+/// Helper function that loads an Intcode computer, from a day's input, a program file or a session saved as JSON.
+pub fn read_computer(source: &str) -> Result<IntCodeComputer, String> {
+    if source.ends_with(".json") {
+        return IntCodeComputer::load(source);
+    }
+    let program = read_program(source).map_err(|e| e.to_string())?;
+    IntCodeComputer::parse(&program).map_err(|e| e.to_string())
+}
+
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
@@ -1,4 +1,5 @@
 /// Encapsulates code that interacts with solution functions.
+use std::cell::Cell;
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{stdout, Write};
@@ -6,17 +7,29 @@ use std::process::Output;
 use std::time::{Duration, Instant};
 use std::{cmp, env, process};
 
+use crate::intcode::profile;
 use crate::template::ANSI_BOLD;
 use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
 
 pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
     let part_str = format!("Part {part}");
+    let intcode_profile = Cell::new(None);
+    profile::take();
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, duration, samples) = run_timed(func, input, |result| {
+        // Only the first run is profiled, not the benchmark samples.
+        intcode_profile.set(profile::take());
+        profile::pause(true);
+        print_result(result, &part_str, "");
+    });
+    profile::pause(false);
 
     print_result(&result, &part_str, &format_duration(&duration, samples));
 
+    if let Some(intcode_profile) = intcode_profile.take() {
+        profile::report(&intcode_profile, &format!("{day}-{part}"));
+    }
+
     if let Some(result) = result {
         submit_result(result, day, part);
     }